@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dyno::provider::{Provider, Request};
+use dyno::Tag;
+
+struct Num<const N: usize>;
+
+impl<'a, const N: usize> Tag<'a> for Num<N> {
+    type Type = usize;
+}
+
+struct Many;
+
+macro_rules! provide_nums {
+    ($request:ident, $($n:literal)*) => {
+        $($request.provide::<Num<$n>>($n);)*
+    };
+}
+
+impl Provider for Many {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        provide_nums!(request, 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19);
+    }
+}
+
+fn match_20_tags(c: &mut Criterion) {
+    let provider: &dyn Provider = &Many;
+    c.bench_function("match first of 20 tags", |b| {
+        b.iter(|| black_box(provider).request::<Num<0>>())
+    });
+    c.bench_function("match last of 20 tags", |b| {
+        b.iter(|| black_box(provider).request::<Num<19>>())
+    });
+    c.bench_function("match none of 20 tags", |b| {
+        b.iter(|| black_box(provider).request::<Num<20>>())
+    });
+}
+
+criterion_group!(benches, match_20_tags);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dyno::{tag, TagValue, Tagged};
+
+fn downcast(c: &mut Criterion) {
+    let value = TagValue::<tag::Value<u64>>(5);
+    let tagged: &dyn Tagged = &value;
+    c.bench_function("downcast_ref", |b| {
+        b.iter(|| *black_box(tagged).downcast_ref::<tag::Value<u64>>().unwrap())
+    });
+    c.bench_function("downcast_ref_unchecked", |b| {
+        // SAFETY: `tagged` is tagged with `tag::Value<u64>`.
+        b.iter(|| unsafe { *black_box(tagged).downcast_ref_unchecked::<tag::Value<u64>>() })
+    });
+}
+
+criterion_group!(benches, downcast);
+criterion_main!(benches);
@@ -0,0 +1,146 @@
+//! Derive macros for `dyno`. These are re-exported by `dyno` when the
+//! `derive` feature is enabled, and should be used through it.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Index, LitStr, Member, Type};
+
+/// Derive `dyno::provider::Provider` for a struct.
+///
+/// Fields marked `#[provide(ref = "Tag")]` are provided by reference under the
+/// given tag, and fields marked `#[provide(delegate)]` must themselves
+/// implement `Provider`, and are forwarded the request. Fields are consulted
+/// in declaration order.
+///
+/// The derived `provided_tags` advertises the tags of `ref` fields, and those
+/// advertised by `delegate` fields.
+///
+/// ```
+/// use dyno::provider::{Provider, Request};
+/// use dyno::tag;
+///
+/// struct Inner;
+///
+/// impl Provider for Inner {
+///     fn provide<'a>(&'a self, request: &mut Request<'a>) {
+///         request.provide::<tag::Value<u32>>(5);
+///     }
+/// }
+///
+/// #[derive(Provider)]
+/// struct Wrapper {
+///     #[provide(ref = "tag::Ref<str>")]
+///     name: String,
+///     #[provide(delegate)]
+///     inner: Inner,
+/// }
+/// ```
+///
+/// Unknown options are rejected:
+///
+/// ```compile_fail
+/// use dyno::provider::Provider;
+///
+/// #[derive(Provider)]
+/// struct Wrapper {
+///     #[provide(value)]
+///     name: String,
+/// }
+/// ```
+///
+/// As are enums:
+///
+/// ```compile_fail
+/// use dyno::provider::Provider;
+///
+/// #[derive(Provider)]
+/// enum Either {
+///     Left,
+///     Right,
+/// }
+/// ```
+#[proc_macro_derive(Provider, attributes(provide))]
+pub fn derive_provider(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`Provider` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut provisions = Vec::new();
+    let mut tags = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        };
+        for attr in &field.attrs {
+            if !attr.path().is_ident("provide") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("delegate") {
+                    provisions.push(quote! {
+                        ::dyno::provider::Provider::provide(&self.#member, request);
+                    });
+                    tags.push(quote! {
+                        ::dyno::provider::__private::merge_tags(
+                            &mut tags,
+                            ::dyno::provider::Provider::provided_tags(&self.#member),
+                        );
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("ref") {
+                    let tag: Type = meta.value()?.parse::<LitStr>()?.parse()?;
+                    provisions.push(quote! {
+                        request.provide::<#tag>(&self.#member);
+                    });
+                    tags.push(quote! {
+                        ::dyno::provider::__private::push_tag(
+                            &mut tags,
+                            ::dyno::provider::req_tag_id::<#tag>(),
+                        );
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `delegate` or `ref = \"...\"`"))
+                }
+            })?;
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::dyno::provider::Provider for #name #ty_generics #where_clause {
+            fn provide<'__dyno_a>(
+                &'__dyno_a self,
+                request: &mut ::dyno::provider::Request<'__dyno_a>,
+            ) {
+                #(#provisions)*
+            }
+
+            ::dyno::__derive_provided_tags! {
+                fn provided_tags(
+                    &self,
+                ) -> ::dyno::provider::__private::Vec<::core::any::TypeId> {
+                    let mut tags = ::dyno::provider::__private::Vec::new();
+                    #(#tags)*
+                    tags
+                }
+            }
+        }
+    })
+}
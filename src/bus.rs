@@ -0,0 +1,63 @@
+//! A minimal event bus built on `dyn Tagged`, where handlers subscribe to
+//! values by tag.
+
+use crate::{Tag, TagValue, Tagged};
+use alloc::{boxed::Box, vec::Vec};
+use core::any::TypeId;
+
+/// Dispatches published values to the handlers which subscribed to their tag.
+///
+/// Handlers may borrow from the environment for `'a`, and published values
+/// may borrow for `'a` as well.
+pub struct Bus<'a> {
+    handlers: Vec<(TypeId, Handler<'a>)>,
+}
+
+/// Implementation detail: A type-erased handler subscribed to a `Bus`.
+type Handler<'a> = Box<dyn Fn(&(dyn Tagged<'a> + 'a)) + 'a>;
+
+impl<'a> Bus<'a> {
+    /// Create a `Bus` with no subscribers.
+    pub fn new() -> Self {
+        Bus {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Subscribe `handler` to values published with the tag `I`.
+    pub fn subscribe<I, F>(&mut self, handler: F) -> &mut Self
+    where
+        I: Tag<'a>,
+        F: Fn(&I::Type) + 'a,
+    {
+        self.handlers.push((
+            TypeId::of::<I>(),
+            Box::new(move |tagged| {
+                if let Some(value) = tagged.downcast_ref::<I>() {
+                    handler(value);
+                }
+            }),
+        ));
+        self
+    }
+
+    /// Publish `value` to all handlers subscribed to the tag `I`, in the order
+    /// they subscribed.
+    pub fn publish<I>(&self, value: I::Type)
+    where
+        I: Tag<'a>,
+    {
+        let value = TagValue::<'a, I>(value);
+        for (id, handler) in &self.handlers {
+            if *id == TypeId::of::<I>() {
+                handler(&value);
+            }
+        }
+    }
+}
+
+impl<'a> Default for Bus<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,33 @@
+//! Bridging between `core::any::Any` and `dyn Tagged<'static>`.
+//!
+//! As `Any` requires its values to be `'static`, these conversions are only
+//! available for `'static` types, using the `tag::Value<T>` tag. For such
+//! types the mapping is lossless.
+
+use crate::{tag, TagValue};
+
+#[cfg(feature = "alloc")]
+use crate::Tagged;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use core::any::Any;
+
+/// Tag a `'static` value, such as one extracted from a `dyn Any`, with the
+/// `tag::Value<T>` tag.
+pub fn from_any<T: 'static>(value: T) -> TagValue<'static, tag::Value<T>> {
+    TagValue(value)
+}
+
+/// Convert a boxed `dyn Tagged<'static>` tagged with `tag::Value<T>` back into
+/// a `Box<dyn Any>`.
+///
+/// If the value is not tagged with `tag::Value<T>`, it is returned unchanged.
+#[cfg(feature = "alloc")]
+pub fn into_any<T: 'static>(
+    value: Box<dyn Tagged<'static>>,
+) -> Result<Box<dyn Any>, Box<dyn Tagged<'static>>> {
+    value
+        .downcast_box::<tag::Value<T>>()
+        .map(|value| value as Box<dyn Any>)
+}
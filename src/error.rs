@@ -0,0 +1,24 @@
+//! Interop with the `Error::provide` API from `std`.
+//!
+//! This allows values provided by a `dyn std::error::Error` to be looked up
+//! through a `dyno::provider::Request`.
+//!
+//! As `Error::provide` is not yet stable, this module requires the
+//! `error-provide` feature and a nightly compiler.
+
+use crate::provider::Request;
+use crate::tag;
+use std::error::{self, Error};
+
+/// Forwards a request for `tag::Ref<T>` to `error`'s `Error::provide`
+/// implementation, using `std::error::request_ref`.
+pub fn provide_ref<'a, T>(error: &'a (dyn Error + 'a), request: &mut Request<'a>)
+where
+    T: ?Sized + 'static,
+{
+    if request.is::<tag::Ref<T>>() {
+        if let Some(value) = error::request_ref::<T>(error) {
+            request.provide::<tag::Ref<T>>(value);
+        }
+    }
+}
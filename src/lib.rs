@@ -1,14 +1,29 @@
 #![no_std]
+#![cfg_attr(feature = "error-provide", feature(error_generic_member_access))]
 use core::any::TypeId;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[cfg(feature = "alloc")]
 use alloc::boxed::Box;
 
+#[cfg(feature = "alloc")]
+pub mod bus;
+pub mod compat;
+#[cfg(feature = "error-provide")]
+pub mod error;
+pub mod prelude;
 pub mod provider;
 pub mod tag;
+mod tagged;
+#[cfg(feature = "trace")]
+pub mod trace;
+
+pub use tagged::TagValue;
 
 /// This trait is implemented by specific `Tag` types in order to allow
 /// describing a type which can be requested for a given lifetime `'a`.
@@ -16,6 +31,11 @@ pub mod tag;
 /// A few example implementations for type-driven `Tag`s can be found in the
 /// [`tag`] module, although crates may also implement their own tags for more
 /// complex types with internal lifetimes.
+///
+/// Tags are usually zero-sized markers, but may also carry small amounts of
+/// data, such as a format selector. A request may be made with a specific tag
+/// value using [`provider::request_with_tag`], which providers can inspect
+/// with [`provider::Request::tag_data`].
 pub trait Tag<'a>: Sized + 'static {
     /// The type of values which may be tagged by this `Tag` for the given
     /// lifetime.
@@ -23,46 +43,83 @@ pub trait Tag<'a>: Sized + 'static {
 }
 
 mod private {
-    pub trait Sealed {}
+    use core::any::TypeId;
+
+    /// Internal hooks used by `provider::Request`.
+    ///
+    /// Each check is a single dynamic call, as requests are matched against
+    /// many tags in a typical `provide` implementation.
+    pub trait Sealed {
+        /// Whether this value answers for the tag with the given `TypeId`.
+        /// Values which answer for multiple tags, such as `provider::Batch`,
+        /// may return `true` for more than one `id`.
+        fn matches_id(&self, id: TypeId) -> bool;
+
+        /// A pointer to the value tagged with `id`, which has the
+        /// representation of that tag's `Type`, if this value answers for it.
+        fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()>;
+
+        /// Whether this value is a `provider::Request` slot which has already
+        /// been filled. Only overridden by the slot types in `provider`.
+        fn is_fulfilled(&self) -> bool {
+            false
+        }
+
+        /// Whether the value tagged with `id` is a `provider::Request` slot
+        /// which has already been filled. Only overridden by the slot types
+        /// in `provider`.
+        fn is_slot_fulfilled(&self, _id: TypeId) -> bool {
+            false
+        }
+
+        /// A pointer to the tag value `I` carried by the `provider::Request`
+        /// slot tagged with `id`, which is the request id for `I`. Only
+        /// overridden by the slot types in `provider`.
+        fn tag_data(&self, _id: TypeId) -> Option<*const ()> {
+            None
+        }
+
+        /// Whether the `provider::Request` this value backs has been
+        /// cancelled. Only overridden by the slot types in `provider`.
+        fn is_cancelled(&self) -> bool {
+            false
+        }
+
+        /// A pointer to the `Box<dyn Tagged<'a> + 'a>` holding the slot tagged
+        /// with `id`, if this value is a batch of single-slot requests, such
+        /// as `provider::Batch`. Only overridden by `provider::Batch`.
+        fn batch_slot(&mut self, _id: TypeId) -> Option<*mut ()> {
+            None
+        }
+    }
+}
+
+#[cold]
+#[track_caller]
+fn expect_downcast_failed<'a, I: Tag<'a>>(actual: TypeId) -> ! {
+    panic!(
+        "expected a value tagged with `{}` ({:?}), found a value tagged with {:?}",
+        core::any::type_name::<I>(),
+        TypeId::of::<I>(),
+        actual
+    )
 }
 
 /// Sealed trait representing a type-erased tagged object.
 ///
 /// Instances of `Tagged<'a>` may be created using the `tag_ref` and related
 /// methods.
+///
+/// # Safety
+///
+/// This trait is sealed, and is only implemented within this crate. The value
+/// returned by `tag_id` must be the `TypeId` of a `Tag` whose `Type` has the
+/// same representation as the implementing type.
 pub unsafe trait Tagged<'a>: private::Sealed + 'a {
     /// The `TypeId` of the `Tag` this value was tagged with.
     fn tag_id(&self) -> TypeId;
 }
 
-/// Internal wrapper type with the same representation as a known external type.
-///
-/// This type exists to encode the specific `Tag` implementation which a value
-/// is associated with into the type, such that when it is type erased into a
-/// `dyn Tagged<'a>`, that information is preserved.
-#[repr(transparent)]
-struct TaggedImpl<'a, I>
-where
-    I: Tag<'a>,
-{
-    _value: I::Type,
-}
-
-impl<'a, I> private::Sealed for TaggedImpl<'a, I> where I: Tag<'a> {}
-
-unsafe impl<'a, I> Tagged<'a> for TaggedImpl<'a, I>
-where
-    I: Tag<'a>,
-{
-    fn tag_id(&self) -> TypeId {
-        TypeId::of::<I>()
-    }
-}
-
-// FIXME: This should also handle the cases for `dyn Tagged<'a> + Send`,
-// `dyn Tagged<'a> + Send + Sync` and `dyn Tagged<'a> + Sync`...
-//
-// Should be easy enough to do it with a macro...
 impl<'a> dyn Tagged<'a> {
     /// Tag a reference to a concrete type with a given `Tag`.
     ///
@@ -72,9 +129,9 @@ impl<'a> dyn Tagged<'a> {
     where
         I: Tag<'a>,
     {
-        // SAFETY: `TaggedImpl<'a, I>` has the same representation as `I::Type`
+        // SAFETY: `TagValue<'a, I>` has the same representation as `I::Type`
         // due to `#[repr(transparent)]`.
-        unsafe { &*(value as *const I::Type as *const TaggedImpl<'a, I>) }
+        unsafe { &*(value as *const I::Type as *const TagValue<'a, I>) }
     }
 
     /// Tag a reference to a concrete type with a given `Tag`.
@@ -85,9 +142,9 @@ impl<'a> dyn Tagged<'a> {
     where
         I: Tag<'a>,
     {
-        // SAFETY: `TaggedImpl<'a, I>` has the same representation as `I::Type`
+        // SAFETY: `TagValue<'a, I>` has the same representation as `I::Type`
         // due to `#[repr(transparent)]`.
-        unsafe { &mut *(value as *mut I::Type as *mut TaggedImpl<'a, I>) }
+        unsafe { &mut *(value as *mut I::Type as *mut TagValue<'a, I>) }
     }
 
     /// Tag a Box of a concrete type with a given `Tag`.
@@ -99,67 +156,228 @@ impl<'a> dyn Tagged<'a> {
     where
         I: Tag<'a>,
     {
-        // SAFETY: `TaggedImpl<'a, I>` has the same representation as `I::Type`
+        // SAFETY: `TagValue<'a, I>` has the same representation as `I::Type`
         // due to `#[repr(transparent)]`.
-        unsafe { Box::from_raw(Box::into_raw(value) as *mut TaggedImpl<'a, I>) }
+        unsafe { Box::from_raw(Box::into_raw(value) as *mut TagValue<'a, I>) }
     }
 
-    /// Returns `true` if the dynamic type is tagged with `I`.
-    #[inline]
-    pub fn is<I>(&self) -> bool
+    /// Tag a Box of a concrete `Send` type with a given `Tag`, preserving the
+    /// `Send` bound on the resulting trait object.
+    ///
+    /// ```compile_fail
+    /// use dyno::{tag, Tagged};
+    /// use std::rc::Rc;
+    ///
+    /// // `Rc<u32>` is not `Send`, so cannot be tagged with `tag_box_send`.
+    /// let _ = <dyn Tagged>::tag_box_send::<tag::Value<Rc<u32>>>(Box::new(Rc::new(5)));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn tag_box_send<I>(value: Box<I::Type>) -> Box<dyn Tagged<'a> + Send>
     where
         I: Tag<'a>,
+        I::Type: Send,
     {
-        self.tag_id() == TypeId::of::<I>()
+        // SAFETY: `TagValue<'a, I>` has the same representation as `I::Type`
+        // due to `#[repr(transparent)]`.
+        unsafe { Box::from_raw(Box::into_raw(value) as *mut TagValue<'a, I>) }
     }
+}
 
-    /// Returns some reference to the dynamic value if it is tagged with `I`,
-    /// or `None` if it isn't.
-    #[inline]
-    pub fn downcast_ref<I>(&self) -> Option<&I::Type>
-    where
-        I: Tag<'a>,
-    {
-        if self.is::<I>() {
-            // SAFETY: Just checked whether we're pointing to a
-            // `TaggedImpl<'a, I>`, which was cast to from an `I::Type`.
-            unsafe { Some(&*(self as *const dyn Tagged<'a> as *const I::Type)) }
-        } else {
-            None
-        }
-    }
+// Methods for inspecting and downcasting tagged values, which are available on
+// `dyn Tagged<'a>` along with its `Send` and `Sync` forms.
+macro_rules! dyn_tagged_impls {
+    ($($dyn:ty),*) => {$(
+        impl<'a> $dyn {
+            /// Returns `true` if the dynamic type is tagged with `I`.
+            #[inline]
+            pub fn is<I>(&self) -> bool
+            where
+                I: Tag<'a>,
+            {
+                self.tag_id() == TypeId::of::<I>()
+            }
 
-    /// Returns some reference to the dynamic value if it is tagged with `I`,
-    /// or `None` if it isn't.
-    #[inline]
-    pub fn downcast_mut<I>(&mut self) -> Option<&mut I::Type>
-    where
-        I: Tag<'a>,
-    {
-        if self.is::<I>() {
-            // SAFETY: Just checked whether we're pointing to a
-            // `TaggedImpl<'a, I>`, which was cast to from an `I::Type`.
-            unsafe { Some(&mut *(self as *mut dyn Tagged<'a> as *mut I::Type)) }
-        } else {
-            None
-        }
-    }
+            /// Returns `true` if `other` is tagged with the same tag as this
+            /// value.
+            #[inline]
+            pub fn same_tag_as(&self, other: &(dyn Tagged<'a> + 'a)) -> bool {
+                self.tag_id() == other.tag_id()
+            }
 
-    #[inline]
-    #[cfg(feature = "alloc")]
-    pub fn downcast_box<I>(self: Box<Self>) -> Result<Box<I::Type>, Box<Self>>
-    where
-        I: Tag<'a>,
-    {
-        if self.is::<I>() {
-            unsafe {
-                // SAFETY: Just checked whether we're pointing to a
-                // `TaggedImpl<'a, I>`, which was cast to from an `I::Type`.
-                let raw: *mut dyn Tagged<'a> = Box::into_raw(self);
-                Ok(Box::from_raw(raw as *mut I::Type))
+            /// Checks the invariants relied upon when casting the dynamic value
+            /// to `I::Type` in debug builds, panicking with a descriptive
+            /// message if they do not hold.
+            #[inline]
+            #[track_caller]
+            fn debug_assert_tagged<I>(&self)
+            where
+                I: Tag<'a>,
+            {
+                debug_assert!(
+                    self.tag_id() == TypeId::of::<I>(),
+                    "dyno: value is not tagged with `{}`",
+                    core::any::type_name::<I>()
+                );
+                debug_assert!(
+                    core::mem::size_of_val(self) == core::mem::size_of::<I::Type>()
+                        && core::mem::align_of_val(self)
+                            == core::mem::align_of::<I::Type>(),
+                    "dyno: value tagged with `{}` does not have the layout of \
+                     `{}`",
+                    core::any::type_name::<I>(),
+                    core::any::type_name::<I::Type>()
+                );
+            }
+
+            /// Returns some reference to the dynamic value if it is tagged with
+            /// `I`, or `None` if it isn't.
+            #[inline]
+            pub fn downcast_ref<I>(&self) -> Option<&I::Type>
+            where
+                I: Tag<'a>,
+            {
+                if self.is::<I>() {
+                    self.debug_assert_tagged::<I>();
+                    // SAFETY: Just checked whether we're pointing to a
+                    // `TagValue<'a, I>`, which was cast to from an
+                    // `I::Type`.
+                    unsafe { Some(&*(self as *const Self as *const I::Type)) }
+                } else {
+                    None
+                }
+            }
+
+            /// Returns a reference to the dynamic value if it is tagged with
+            /// `I`, or `default` if it isn't.
+            #[inline]
+            pub fn downcast_ref_or<'b, I>(
+                &'b self,
+                default: &'b I::Type,
+            ) -> &'b I::Type
+            where
+                I: Tag<'a>,
+            {
+                self.downcast_ref::<I>().unwrap_or(default)
+            }
+
+            /// Returns a reference to the dynamic value, which must be tagged
+            /// with `I`.
+            ///
+            /// # Panics
+            ///
+            /// Panics with the expected and actual tag ids if the value is not
+            /// tagged with `I`.
+            #[track_caller]
+            pub fn expect_downcast_ref<I>(&self) -> &I::Type
+            where
+                I: Tag<'a>,
+            {
+                let actual = self.tag_id();
+                match self.downcast_ref::<I>() {
+                    Some(value) => value,
+                    None => expect_downcast_failed::<I>(actual),
+                }
+            }
+
+            /// Returns a mutable reference to the dynamic value, which must be
+            /// tagged with `I`.
+            ///
+            /// # Panics
+            ///
+            /// Panics with the expected and actual tag ids if the value is not
+            /// tagged with `I`.
+            #[track_caller]
+            pub fn expect_downcast_mut<I>(&mut self) -> &mut I::Type
+            where
+                I: Tag<'a>,
+            {
+                let actual = self.tag_id();
+                match self.downcast_mut::<I>() {
+                    Some(value) => value,
+                    None => expect_downcast_failed::<I>(actual),
+                }
+            }
+
+            /// Returns some reference to the dynamic value if it is tagged with
+            /// `I`, or `None` if it isn't.
+            #[inline]
+            pub fn downcast_mut<I>(&mut self) -> Option<&mut I::Type>
+            where
+                I: Tag<'a>,
+            {
+                if self.is::<I>() {
+                    self.debug_assert_tagged::<I>();
+                    // SAFETY: Just checked whether we're pointing to a
+                    // `TagValue<'a, I>`, which was cast to from an
+                    // `I::Type`.
+                    unsafe { Some(&mut *(self as *mut Self as *mut I::Type)) }
+                } else {
+                    None
+                }
+            }
+
+            /// Returns a reference to the dynamic value, assuming it is tagged
+            /// with `I`, without checking.
+            ///
+            /// # Safety
+            ///
+            /// The value must be tagged with `I`, such as when `self.is::<I>()`
+            /// has already been checked. This is verified with a
+            /// `debug_assert!`.
+            #[inline]
+            pub unsafe fn downcast_ref_unchecked<I>(&self) -> &I::Type
+            where
+                I: Tag<'a>,
+            {
+                self.debug_assert_tagged::<I>();
+                &*(self as *const Self as *const I::Type)
+            }
+
+            /// Returns a mutable reference to the dynamic value, assuming it is
+            /// tagged with `I`, without checking.
+            ///
+            /// # Safety
+            ///
+            /// The value must be tagged with `I`, such as when `self.is::<I>()`
+            /// has already been checked. This is verified with a
+            /// `debug_assert!`.
+            #[inline]
+            pub unsafe fn downcast_mut_unchecked<I>(&mut self) -> &mut I::Type
+            where
+                I: Tag<'a>,
+            {
+                self.debug_assert_tagged::<I>();
+                &mut *(self as *mut Self as *mut I::Type)
+            }
+
+            #[inline]
+            #[cfg(feature = "alloc")]
+            pub fn downcast_box<I>(
+                self: Box<Self>,
+            ) -> Result<Box<I::Type>, Box<Self>>
+            where
+                I: Tag<'a>,
+            {
+                if self.is::<I>() {
+                    self.debug_assert_tagged::<I>();
+                    unsafe {
+                        // SAFETY: Just checked whether we're pointing to a
+                        // `TagValue<'a, I>`, which was cast to from an
+                        // `I::Type`.
+                        let raw: *mut Self = Box::into_raw(self);
+                        Ok(Box::from_raw(raw as *mut I::Type))
+                    }
+                } else {
+                    Err(self)
+                }
             }
-        } else {
-            Err(self)
         }
-    }
+    )*};
 }
+
+dyn_tagged_impls!(
+    dyn Tagged<'a>,
+    dyn Tagged<'a> + Send,
+    dyn Tagged<'a> + Sync,
+    dyn Tagged<'a> + Send + Sync
+);
@@ -0,0 +1,9 @@
+//! Re-exports of the most commonly used traits, types and tags.
+//!
+//! ```
+//! use dyno::prelude::*;
+//! ```
+
+pub use crate::provider::{Provider, Request};
+pub use crate::tag::{Optional, Ref, RefMut, Value};
+pub use crate::{Tag, TagValue, Tagged};
@@ -3,7 +3,19 @@
 //! This provides a similar API to my `object_provider` crate, built on top of
 //! `dyno`.
 
-use crate::{Tag, Tagged};
+use crate::{private, tag, Tag, Tagged};
+use core::any::TypeId;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "derive")]
+pub use dyno_derive::Provider;
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::cell::RefCell;
 
 /// An untyped request for a value of a specific type.
 ///
@@ -25,13 +37,204 @@ impl<'a> Request<'a> {
         unsafe { &mut *(t as *mut (dyn Tagged<'a> + 'a) as *mut Request<'a>) }
     }
 
+    /// Views a type-erased tagged value as a `Request<'a>`.
+    ///
+    /// This allows downstream crates to build alternative provider frontends
+    /// backed by their own request slots. The request is for the tag `I` if
+    /// `tagged` exposes a slot for `tag::Optional<I>`, such as a
+    /// `&mut Option<I::Type>` tagged with `<dyn Tagged>::tag_mut`. This is
+    /// safe, as `Tagged` guarantees that every slot has the representation of
+    /// its tag's `Type`, and `Request<'a>` has the same layout as
+    /// `dyn Tagged<'a> + 'a`.
+    ///
+    /// Only the request slots created by this module track whether they have
+    /// been fulfilled. For other tagged values, such as those created with
+    /// `tag_mut`, `is_fulfilled` always returns `false`, so combinators such as
+    /// `Or` consult every provider, and `provide_erased` overwrites any
+    /// existing value. `provide` and the other typed provision methods still
+    /// never overwrite an existing value. Frontends which need these
+    /// guarantees should use [`request_into`] instead.
+    pub fn new<'b>(tagged: &'b mut (dyn Tagged<'a> + 'a)) -> &'b mut Self {
+        Self::wrap_tagged(tagged)
+    }
+
     /// Check if the request is for a value with the given tag `I`. If it is,
     /// returns `true`.
     pub fn is<I>(&self) -> bool
     where
         I: Tag<'a>,
     {
-        self.tagged.is::<ReqTag<I>>()
+        let matched = private::Sealed::matches_id(&self.tagged, TypeId::of::<ReqTag<I>>());
+        #[cfg(feature = "trace")]
+        crate::trace::emit(
+            TypeId::of::<ReqTag<I>>(),
+            core::any::type_name::<I>(),
+            matched,
+        );
+        matched
+    }
+
+    /// Check if the request is for a `tag::Ref<T>`.
+    pub fn wants_ref<T>(&self) -> bool
+    where
+        T: ?Sized + 'static,
+    {
+        self.is::<tag::Ref<T>>()
+    }
+
+    /// Check if the request is for a `tag::Value<T>`.
+    pub fn wants_value<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.is::<tag::Value<T>>()
+    }
+
+    /// Assert that the request is for a value with the given tag `I`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the request is not for `I`. This is intended for use in
+    /// tests of provider dispatch logic.
+    #[track_caller]
+    pub fn expect<I>(&self)
+    where
+        I: Tag<'a>,
+    {
+        if !self.is::<I>() {
+            panic!("expected a request for `{}`", core::any::type_name::<I>());
+        }
+    }
+
+    /// Check if the request is for a value identified by the request id `id`.
+    ///
+    /// The request id for a tag `I` is the `TypeId` of `tag::Optional<I>`, as
+    /// requests are implemented as `Option<I::Type>` slots tagged with
+    /// `tag::Optional<I>`. This allows generic code, such as a proxy
+    /// forwarding requests elsewhere, to inspect requests dynamically. The
+    /// request id for a tag may be obtained with [`req_tag_id`].
+    pub fn matches_id(&self, id: TypeId) -> bool {
+        let matched = private::Sealed::matches_id(&self.tagged, id);
+        #[cfg(feature = "trace")]
+        crate::trace::emit(id, crate::trace::UNKNOWN_TAG, matched);
+        matched
+    }
+
+    /// Check if the request is for a value identified by any of the request
+    /// ids in `ids`.
+    ///
+    /// Request ids may be obtained with [`req_tag_id`]. This allows a provider
+    /// to cheaply gate a block of provisions for a set of related tags.
+    pub fn matches_any(&self, ids: &[TypeId]) -> bool {
+        ids.iter().any(|&id| self.matches_id(id))
+    }
+
+    /// Returns a request for only the value identified by the request id
+    /// `id`, if the request is for it.
+    ///
+    /// For requests which answer for several ids, such as those made with a
+    /// `Batch`, this exposes only the slot for `id`, so that the other slots
+    /// may not be fulfilled through it.
+    #[cfg(feature = "alloc")]
+    fn single_slot(&mut self, id: TypeId) -> Option<&mut Self> {
+        if !self.matches_id(id) {
+            return None;
+        }
+        match private::Sealed::batch_slot(&mut self.tagged, id) {
+            Some(slot) => {
+                // SAFETY: The slot is a `Box<dyn Tagged<'a> + 'a>` owned by
+                // the `Batch<'a>` this request points to.
+                let slot = unsafe { &mut *(slot as *mut Box<dyn Tagged<'a> + 'a>) };
+                Some(Self::wrap_tagged(&mut **slot))
+            }
+            None => Some(self),
+        }
+    }
+
+    /// Fulfills the request with a type-erased value, if the request is for
+    /// the value's tag.
+    ///
+    /// `value` must be tagged with `tag::Optional<I>`, such as a
+    /// `&mut Option<I::Type>` tagged with `<dyn Tagged>::tag_mut`. If the
+    /// request is for `I` and has not already been fulfilled, the contents of
+    /// `value` are moved into the request, leaving `None` behind.
+    pub fn provide_erased(&mut self, value: &mut (dyn Tagged<'a> + 'a)) -> &mut Self {
+        let id = value.tag_id();
+        #[cfg(feature = "trace")]
+        crate::trace::emit(
+            id,
+            crate::trace::UNKNOWN_TAG,
+            private::Sealed::matches_id(&self.tagged, id),
+        );
+        if private::Sealed::is_slot_fulfilled(&self.tagged, id) {
+            return self;
+        }
+        if let Some(slot) = private::Sealed::slot_mut(&mut self.tagged, id) {
+            let size = core::mem::size_of_val(value);
+            // SAFETY: Both `slot` and `value` are tagged with `id`, so they are
+            // both `Option<I::Type>` values for the same `I`. As the slot is
+            // unfulfilled, swapping them moves the value into the request and
+            // leaves `None` behind.
+            unsafe {
+                core::ptr::swap_nonoverlapping(
+                    slot as *mut MaybeUninit<u8>,
+                    value as *mut (dyn Tagged<'a> + 'a) as *mut MaybeUninit<u8>,
+                    size,
+                );
+            }
+        }
+        self
+    }
+
+    /// Returns the request's slot for the tag `I`, if the request is for `I`.
+    fn slot_mut<I>(&mut self) -> Option<&mut Option<I::Type>>
+    where
+        I: Tag<'a>,
+    {
+        let slot = private::Sealed::slot_mut(&mut self.tagged, TypeId::of::<ReqTag<I>>());
+        #[cfg(feature = "trace")]
+        crate::trace::emit(
+            TypeId::of::<ReqTag<I>>(),
+            core::any::type_name::<I>(),
+            slot.is_some(),
+        );
+        let slot = slot?;
+        // SAFETY: The slot is tagged with `ReqTag<I>`, so has the
+        // representation of `Option<I::Type>`.
+        unsafe { Some(&mut *(slot as *mut Option<I::Type>)) }
+    }
+
+    /// Check if the request was made with [`request_cancellable`], and its
+    /// `CancelToken` has since been cancelled.
+    ///
+    /// Providers which do expensive work may check this to abort early.
+    /// Combinators such as `Or` stop consulting further providers once the
+    /// request has been cancelled. Other kinds of request, such as those made
+    /// with a `Batch` or `request_with_tag`, are never cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        private::Sealed::is_cancelled(&self.tagged)
+    }
+
+    /// Returns the tag value carried by the request, if it is for `I` and was
+    /// made with [`request_with_tag`].
+    ///
+    /// This allows tags which carry data to influence how they are provided.
+    pub fn tag_data<I>(&self) -> Option<&I>
+    where
+        I: Tag<'a>,
+    {
+        let data = private::Sealed::tag_data(&self.tagged, TypeId::of::<ReqTag<I>>())?;
+        // SAFETY: The data carried by the slot tagged with `ReqTag<I>` is an
+        // `I`.
+        unsafe { Some(&*(data as *const I)) }
+    }
+
+    /// Check if the request has already been fulfilled by a prior provider.
+    ///
+    /// Providers may check this at the start of `provide` to skip expensive
+    /// setup which would have no effect.
+    pub fn is_fulfilled(&self) -> bool {
+        private::Sealed::is_fulfilled(&self.tagged)
     }
 
     /// Attempts to provide a value with the given `Tag` to the request.
@@ -39,58 +242,1482 @@ impl<'a> Request<'a> {
     where
         I: Tag<'a>,
     {
-        if let Some(res @ None) = self.tagged.downcast_mut::<ReqTag<I>>() {
+        if let Some(res @ None) = self.slot_mut::<I>() {
             *res = Some(value);
         }
         self
     }
 
+    /// Attempts to provide a reference borrowed for the request lifetime `'a`
+    /// to the request, if it is for `tag::Ref<T>`.
+    ///
+    /// Within `Provider::provide`, `'a` is the lifetime of `&'a self`, so
+    /// references to fields of the provider may be passed directly, and `T` is
+    /// inferred from the field's type:
+    ///
+    /// ```
+    /// use dyno::provider::{Provider, Request};
+    ///
+    /// struct Config {
+    ///     name: String,
+    /// }
+    ///
+    /// impl Provider for Config {
+    ///     fn provide<'a>(&'a self, request: &mut Request<'a>) {
+    ///         // Equivalent to `request.provide::<tag::Ref<String>>(&self.name)`.
+    ///         request.provide_borrowed(&self.name);
+    ///     }
+    /// }
+    /// ```
+    pub fn provide_borrowed<T>(&mut self, value: &'a T) -> &mut Self
+    where
+        T: ?Sized + 'static,
+    {
+        self.provide::<tag::Ref<T>>(value)
+    }
+
+    /// Attempts to provide `value` to the request if `cond` is `true` and the
+    /// request is for `tag::Ref<T>`.
+    pub fn provide_ref_if<T>(&mut self, cond: bool, value: &'a T) -> &mut Self
+    where
+        T: ?Sized + 'static,
+    {
+        if cond {
+            self.provide::<tag::Ref<T>>(value);
+        }
+        self
+    }
+
+    /// Attempts to provide `value` to the request, if it is for
+    /// `tag::Ref<T>`, or a clone of it, if it is for `tag::Value<T>`.
+    ///
+    /// The value is only cloned if the request is for `tag::Value<T>` and has
+    /// not already been fulfilled.
+    pub fn provide_ref_or_value<T>(&mut self, value: &'a T) -> &mut Self
+    where
+        T: Clone + 'static,
+    {
+        self.provide::<tag::Ref<T>>(value)
+            .provide_with::<tag::Value<T>, _>(|| value.clone())
+    }
+
+    /// Attempts to provide `bytes` reinterpreted as a `&'a T` to the request,
+    /// if it is for `tag::Pod<T>`.
+    ///
+    /// The request is left unfulfilled if `bytes` does not have the size and
+    /// alignment of `T`.
+    #[cfg(feature = "bytemuck")]
+    pub fn provide_pod_bytes<T>(&mut self, bytes: &'a [u8]) -> &mut Self
+    where
+        T: bytemuck::Pod,
+    {
+        self.provide_computed::<tag::Pod<T>, _>(|| bytemuck::try_from_bytes(bytes).ok())
+    }
+
+    /// Attempts to provide a clone of `value` to the request, if it is for
+    /// `tag::Owned<T>`.
+    ///
+    /// The value is only cloned if the request is for `tag::Owned<T>` and has
+    /// not already been fulfilled.
+    pub fn provide_owned<T>(&mut self, value: &'a T) -> &mut Self
+    where
+        T: Clone + 'static,
+    {
+        self.provide_with::<tag::Owned<T>, _>(|| value.clone())
+    }
+
+    /// Attempts to provide an owned `value` to the request, if it is for
+    /// `tag::BorrowedAs<I, U>`, to be lent to the requester as `&U`.
+    pub fn provide_borrowed_as<I, U>(&mut self, value: I::Type) -> &mut Self
+    where
+        I: Tag<'a>,
+        I::Type: core::borrow::Borrow<U>,
+        U: ?Sized + 'static,
+    {
+        self.provide::<tag::BorrowedAs<I, U>>(value)
+    }
+
+    /// Attempts to provide a value with the given `Tag` to the request.
+    ///
+    /// If the request is not for `I`, or has already been fulfilled, the value
+    /// is returned back to the caller.
+    pub fn try_provide<I>(&mut self, value: I::Type) -> Result<&mut Self, I::Type>
+    where
+        I: Tag<'a>,
+    {
+        match self.slot_mut::<I>() {
+            Some(res @ None) => *res = Some(value),
+            _ => return Err(value),
+        }
+        Ok(self)
+    }
+
+    /// Attempts to provide a successful `value` to the request, if it is for
+    /// `tag::Result<T, E>`.
+    pub fn provide_ok<T, E>(&mut self, value: T::Type) -> &mut Self
+    where
+        T: Tag<'a>,
+        E: Tag<'a>,
+    {
+        self.provide::<tag::Result<T, E>>(Ok(value))
+    }
+
+    /// Attempts to provide an `err` to the request, if it is for
+    /// `tag::Result<T, E>`.
+    pub fn provide_err<T, E>(&mut self, err: E::Type) -> &mut Self
+    where
+        T: Tag<'a>,
+        E: Tag<'a>,
+    {
+        self.provide::<tag::Result<T, E>>(Err(err))
+    }
+
+    /// Attempts to provide `I::Type::default()` to the request, if it is for
+    /// `I`.
+    ///
+    /// This is useful as a last-resort provision at the end of a chain.
+    pub fn provide_default<I>(&mut self) -> &mut Self
+    where
+        I: Tag<'a>,
+        I::Type: Default,
+    {
+        self.provide_with::<I, _>(Default::default)
+    }
+
     /// Attempts to provide a value with the given `Tag` to the request.
     pub fn provide_with<I, F>(&mut self, f: F) -> &mut Self
     where
         I: Tag<'a>,
         F: FnOnce() -> I::Type,
     {
-        if let Some(res @ None) = self.tagged.downcast_mut::<ReqTag<I>>() {
+        if let Some(res @ None) = self.slot_mut::<I>() {
             *res = Some(f());
         }
         self
     }
+
+    /// Attempts to provide a value computed by `f` to the request, if it is
+    /// for `I`.
+    ///
+    /// Unlike `provide_with`, `f` may decline to provide a value by returning
+    /// `None`, leaving the request unfulfilled for later providers.
+    pub fn provide_computed<I, F>(&mut self, f: F) -> &mut Self
+    where
+        I: Tag<'a>,
+        F: FnOnce() -> Option<I::Type>,
+    {
+        if let Some(res @ None) = self.slot_mut::<I>() {
+            *res = f();
+        }
+        self
+    }
+
+    /// Attempts to provide the first item of `iter` to the request, if it is
+    /// for `I`.
+    ///
+    /// The iterator is only advanced if the request is for `I` and has not
+    /// already been fulfilled, in which case exactly one item is consumed.
+    pub fn provide_from_iter<I, It>(&mut self, iter: It) -> &mut Self
+    where
+        I: Tag<'a>,
+        It: IntoIterator<Item = I::Type>,
+    {
+        if let Some(res @ None) = self.slot_mut::<I>() {
+            *res = iter.into_iter().next();
+        }
+        self
+    }
+
+    /// Attempts to provide a value computed by `f` under either of the tags
+    /// `A` or `B`, using `Into` to convert it to the requested type.
+    ///
+    /// The closure is evaluated at most once, and only if the request is for
+    /// `A` or `B`. If the request is for both, only `A` is provided.
+    pub fn provide_either<A, B, T, F>(&mut self, f: F) -> &mut Self
+    where
+        A: Tag<'a>,
+        B: Tag<'a>,
+        T: Into<A::Type> + Into<B::Type>,
+        F: FnOnce() -> T,
+    {
+        if self.is::<A>() {
+            self.provide_with::<A, _>(|| f().into())
+        } else {
+            self.provide_with::<B, _>(|| f().into())
+        }
+    }
+
+    /// Attempts to provide a lazily-computed reference to the request, if it
+    /// is for `tag::Ref<T>`.
+    ///
+    /// The closure is only invoked if the request is for `tag::Ref<T>` and has
+    /// not already been fulfilled.
+    pub fn provide_ref_with<T, F>(&mut self, f: F) -> &mut Self
+    where
+        T: ?Sized + 'static,
+        F: FnOnce() -> &'a T,
+    {
+        self.provide_with::<tag::Ref<T>, F>(f)
+    }
+
+    /// Attempts to provide a value with the given `Tag` to the request, if it
+    /// is a `KeyedRequest` for `I` whose key is equal to `key`.
+    pub fn provide_keyed<I, K>(&mut self, key: &K, value: I::Type) -> &mut Self
+    where
+        I: Tag<'a>,
+        K: PartialEq + 'static,
+    {
+        let slot = private::Sealed::slot_mut(&mut self.tagged, TypeId::of::<KeyedSlot<K, I>>());
+        #[cfg(feature = "trace")]
+        crate::trace::emit(
+            TypeId::of::<ReqTag<I>>(),
+            core::any::type_name::<I>(),
+            slot.is_some(),
+        );
+        if let Some(slot) = slot {
+            // SAFETY: Only a `KeyedRequest<'a, K, I>` exposes a slot for
+            // `KeyedSlot<K, I>`, which is the request itself.
+            let slot = unsafe { &mut *(slot as *mut KeyedRequest<'a, K, I>) };
+            if slot.value.is_none() && slot.key == *key {
+                slot.value = Some(value);
+            }
+        }
+        self
+    }
+
+    /// Attempts to provide a value computed by `f` from the request's
+    /// argument, if it is an `ArgRequest` for `I` with an argument tagged
+    /// with `Arg`.
+    ///
+    /// The closure is only invoked if the request has not already been
+    /// fulfilled.
+    pub fn provide_computed_from<Arg, I, F>(&mut self, f: F) -> &mut Self
+    where
+        Arg: Tag<'a>,
+        I: Tag<'a>,
+        F: FnOnce(&Arg::Type) -> I::Type,
+    {
+        let slot = private::Sealed::slot_mut(&mut self.tagged, TypeId::of::<ArgSlot<Arg, I>>());
+        #[cfg(feature = "trace")]
+        crate::trace::emit(
+            TypeId::of::<ReqTag<I>>(),
+            core::any::type_name::<I>(),
+            slot.is_some(),
+        );
+        if let Some(slot) = slot {
+            // SAFETY: Only an `ArgRequest<'a, Arg, I>` exposes a slot for
+            // `ArgSlot<Arg, I>`, which is the request itself.
+            let slot = unsafe { &mut *(slot as *mut ArgRequest<'a, Arg, I>) };
+            if slot.value.is_none() {
+                slot.value = Some(f(&slot.arg));
+            }
+        }
+        self
+    }
+
+    /// If the request is for a value with the given tag `I`, stores `value`
+    /// in the request and returns the previous contents of the slot.
+    ///
+    /// Unlike `provide`, this overwrites a value placed by a prior provider.
+    /// If the request is not for `I`, `value` is dropped and `None` is
+    /// returned.
+    pub fn replace<I>(&mut self, value: I::Type) -> Option<I::Type>
+    where
+        I: Tag<'a>,
+    {
+        self.slot_mut::<I>()?.replace(value)
+    }
+
+    /// If the request is for a value with the given tag `I`, replaces the
+    /// current value with the result of calling `f` on it.
+    ///
+    /// Unlike `provide`, this is invoked even if the request has already been
+    /// fulfilled, allowing a provider to observe and transform a value placed
+    /// by a prior provider.
+    pub fn with_current<I, F>(&mut self, f: F) -> &mut Self
+    where
+        I: Tag<'a>,
+        F: FnOnce(Option<I::Type>) -> Option<I::Type>,
+    {
+        if let Some(res) = self.slot_mut::<I>() {
+            *res = f(res.take());
+        }
+        self
+    }
 }
 
 pub trait Provider {
-    fn provide<'a>(&'a self, request: &mut Request<'a>);
-}
+    /// Fulfill `request` with values from this provider. The default
+    /// implementation provides nothing.
+    fn provide<'a>(&'a self, _request: &mut Request<'a>) {}
+
+    /// Advertise the request ids of the tags this provider can satisfy, for
+    /// debugging and tooling purposes. Request ids may be obtained with
+    /// [`req_tag_id`].
+    ///
+    /// This is purely informational, and is not consulted when fulfilling
+    /// requests. The default implementation advertises no tags.
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        Vec::new()
+    }
 
-impl dyn Provider {
-    pub fn request<'a, I>(&'a self) -> Option<I::Type>
+    /// Combine this provider with `other`, which will only be consulted for
+    /// requests this provider does not fulfill.
+    fn or<P>(self, other: P) -> Or<Self, P>
     where
-        I: Tag<'a>,
+        Self: Sized,
+        P: Provider,
     {
-        request::<I, _>(|request| self.provide(request))
+        Or(self, other)
     }
 }
 
-/// Create a type-erased `Request<'a>` for the given type tag `I`. The closure
-/// argument will be invoked with a reference to this request, which may be
-/// fulfilled dynamically.
-pub fn request<'a, I, F>(f: F) -> Option<<I as Tag<'a>>::Type>
+/// Like `Provider`, but with exclusive access to the provider while handling
+/// the request, allowing it to provide mutable references into its state.
+///
+/// Requests are made with [`request_mut`]. Provided values borrow the provider
+/// exclusively, so any writes made through them are visible to the provider
+/// once they are dropped.
+pub trait ProviderMut {
+    fn provide_mut<'a>(&'a mut self, request: &mut Request<'a>);
+}
+
+/// Request a value with the given tag `I` from a `ProviderMut`, exclusively
+/// borrowing it for `'a`.
+#[must_use]
+pub fn request_mut<'a, I, P>(provider: &'a mut P) -> Option<<I as Tag<'a>>::Type>
 where
     I: Tag<'a>,
-    F: FnOnce(&mut Request<'a>),
+    P: ?Sized + ProviderMut,
 {
-    let mut result: Option<<I as Tag<'a>>::Type> = None;
-    f(Request::<'a>::wrap_tagged(
-        <dyn Tagged>::tag_mut::<ReqTag<I>>(&mut result),
-    ));
-    result
+    request::<I, _>(move |request| provider.provide_mut(request))
 }
 
-/// Implementation detail: Specific `Tag` tag used by the `Request` code under
-/// the hood.
+/// Provider combinator which consults `A`, and then `B` for any requests not
+/// fulfilled by `A`. Created by [`Provider::or`].
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: Provider, B: Provider> Provider for Or<A, B> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        self.0.provide(request);
+        if !request.is_fulfilled() && !request.is_cancelled() {
+            self.1.provide(request);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        let mut tags = self.0.provided_tags();
+        merge_tags(&mut tags, self.1.provided_tags());
+        tags
+    }
+}
+
+/// Implementation detail: Appends the request ids in `more` which are not
+/// already in `tags`, for combinators advertising the tags of several
+/// providers.
+#[cfg(feature = "alloc")]
+fn merge_tags(tags: &mut Vec<TypeId>, more: Vec<TypeId>) {
+    for id in more {
+        if !tags.contains(&id) {
+            tags.push(id);
+        }
+    }
+}
+
+/// Chain several providers together with [`Provider::or`], such that
+/// `chain!(a, b, c)` is equivalent to `a.or(b).or(c)`.
+///
+/// Earlier providers take priority over later ones.
+#[macro_export]
+macro_rules! chain {
+    ($first:expr $(,)?) => {
+        $first
+    };
+    ($first:expr, $second:expr $(, $rest:expr)* $(,)?) => {
+        $crate::chain!($crate::provider::Provider::or($first, $second) $(, $rest)*)
+    };
+}
+
+pub use crate::chain;
+
+/// Implementation detail: Expands to the `provided_tags` method generated by
+/// `#[derive(Provider)]` if the `alloc` feature of this crate is enabled, as
+/// `cfg` attributes in the generated code would refer to the features of the
+/// deriving crate.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __derive_provided_tags {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __derive_provided_tags {
+    ($($item:tt)*) => {};
+}
+
+/// Implementation detail: Items used by the code generated by
+/// `#[derive(Provider)]`.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __private {
+    use core::any::TypeId;
+
+    pub use alloc::vec::Vec;
+
+    pub fn merge_tags(tags: &mut Vec<TypeId>, more: Vec<TypeId>) {
+        super::merge_tags(tags, more);
+    }
+
+    pub fn push_tag(tags: &mut Vec<TypeId>, id: TypeId) {
+        if !tags.contains(&id) {
+            tags.push(id);
+        }
+    }
+}
+
+impl<T: Provider> Provider for Option<T> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        if let Some(provider) = self {
+            provider.provide(request);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        self.as_ref().map_or_else(Vec::new, Provider::provided_tags)
+    }
+}
+
+impl Provider for () {}
+
+/// Tuples of providers consult each element in order, so earlier elements take
+/// priority over later ones. Later elements are not consulted once the request
+/// has been fulfilled or cancelled.
+macro_rules! tuple_provider_impls {
+    ($(($($name:ident . $idx:tt),+))*) => {$(
+        impl<$($name: Provider),+> Provider for ($($name,)+) {
+            fn provide<'a>(&'a self, request: &mut Request<'a>) {
+                $(
+                    self.$idx.provide(request);
+                    if request.is_fulfilled() || request.is_cancelled() {
+                        return;
+                    }
+                )+
+            }
+
+            #[cfg(feature = "alloc")]
+            fn provided_tags(&self) -> Vec<TypeId> {
+                let mut tags = Vec::new();
+                $(merge_tags(&mut tags, self.$idx.provided_tags());)+
+                tags
+            }
+        }
+    )*};
+}
+
+tuple_provider_impls! {
+    (A.0)
+    (A.0, B.1)
+    (A.0, B.1, C.2)
+    (A.0, B.1, C.2, D.3)
+    (A.0, B.1, C.2, D.3, E.4)
+    (A.0, B.1, C.2, D.3, E.4, F.5)
+    (A.0, B.1, C.2, D.3, E.4, F.5, G.6)
+    (A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7)
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized + Provider> Provider for alloc::sync::Arc<T> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        (**self).provide(request);
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        (**self).provided_tags()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized + Provider> Provider for alloc::rc::Rc<T> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        (**self).provide(request);
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        (**self).provided_tags()
+    }
+}
+
+/// Slices and arrays of providers consult each element in order, so earlier
+/// elements take priority over later ones. Later elements are not consulted
+/// once the request has been fulfilled or cancelled.
+impl<P: Provider> Provider for [P] {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        for provider in self {
+            provider.provide(request);
+            if request.is_fulfilled() || request.is_cancelled() {
+                break;
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        let mut tags = Vec::new();
+        for provider in self {
+            merge_tags(&mut tags, provider.provided_tags());
+        }
+        tags
+    }
+}
+
+impl<P: Provider, const N: usize> Provider for [P; N] {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        self[..].provide(request);
+    }
+
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        self[..].provided_tags()
+    }
+}
+
+/// Create an ad-hoc provider which calls `f` to fulfill each request.
 ///
-/// Composition of `Tag` types!
-struct ReqTag<I>(I);
-impl<'a, I: Tag<'a>> Tag<'a> for ReqTag<I> {
-    type Type = Option<I::Type>;
+/// As `f` must handle requests of any lifetime, it may only provide values
+/// which do not borrow from its captured state, such as clones of owned values
+/// or `'static` references. Providers which hand out references to their own
+/// data should implement `Provider` directly.
+pub fn from_fn<F>(f: F) -> FnProvider<F>
+where
+    F: Fn(&mut Request<'_>),
+{
+    FnProvider(f)
+}
+
+/// Provider which calls a closure to fulfill each request. Created by
+/// [`from_fn`].
+pub struct FnProvider<F>(F);
+
+impl<F> Provider for FnProvider<F>
+where
+    F: Fn(&mut Request<'_>),
+{
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        (self.0)(request);
+    }
 }
+
+/// Provider adapter which translates requests for the tag `To` into requests
+/// for the tag `From` on the inner provider, converting the result with `f`.
+///
+/// This may be used to adapt providers with incompatible tag vocabularies. Both
+/// tags must have a `Type` which does not depend on the request lifetime, such
+/// as `tag::Value<T>`.
+pub struct MapTag<P, From, To, F> {
+    provider: P,
+    f: F,
+    _marker: PhantomData<(From, To)>,
+}
+
+impl<P, From, To, F> MapTag<P, From, To, F> {
+    /// Wrap `provider`, converting values tagged with `From` using `f`.
+    pub fn new(provider: P, f: F) -> Self {
+        MapTag {
+            provider,
+            f,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<P, From, To, F, A, B> Provider for MapTag<P, From, To, F>
+where
+    P: Provider,
+    From: for<'a> Tag<'a, Type = A>,
+    To: for<'a> Tag<'a, Type = B>,
+    F: Fn(A) -> B,
+{
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        if request.is::<To>() {
+            if let Some(value) = self::request::<From, _>(|inner| self.provider.provide(inner)) {
+                request.provide::<To>((self.f)(value));
+            }
+        }
+    }
+
+    /// Advertises `To` if the inner provider advertises `From`.
+    #[cfg(feature = "alloc")]
+    fn provided_tags(&self) -> Vec<TypeId> {
+        let mut tags = Vec::new();
+        if self
+            .provider
+            .provided_tags()
+            .contains(&req_tag_id::<From>())
+        {
+            tags.push(req_tag_id::<To>());
+        }
+        tags
+    }
+}
+
+/// Provider adapter which only forwards requests for an allowlisted set of
+/// tags to the inner provider, preventing it from answering any others.
+///
+/// Tags are identified by their request ids, as returned by [`req_tag_id`].
+/// Requests for several tags, such as those made with a `Batch`, are forwarded
+/// separately for each allowlisted tag, so the inner provider may not fulfill
+/// any of their other tags. Keyed and argument requests for an allowlisted tag
+/// are forwarded regardless of their key or argument.
+#[cfg(feature = "alloc")]
+pub struct Filtered<P> {
+    provider: P,
+    allowed: Vec<TypeId>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Provider> Filtered<P> {
+    /// Wrap `provider`, only allowing requests for the request ids in
+    /// `allowed`.
+    pub fn new(provider: P, allowed: &[TypeId]) -> Self {
+        let mut filtered = Filtered {
+            provider,
+            allowed: Vec::new(),
+        };
+        merge_tags(&mut filtered.allowed, allowed.to_vec());
+        filtered
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Provider> Provider for Filtered<P> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        for &id in &self.allowed {
+            if let Some(request) = request.single_slot(id) {
+                self.provider.provide(request);
+            }
+        }
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        let mut tags = self.provider.provided_tags();
+        tags.retain(|id| self.allowed.contains(id));
+        tags
+    }
+}
+
+/// Provider adapter which memoizes owned values produced by the inner
+/// provider, returning clones of them for subsequent requests.
+///
+/// As provided values may borrow from the provider, only values tagged with
+/// `tag::Value<T>` for `T: Clone + 'static` may be cached, and each such type
+/// must be registered with [`Cached::cache`]. Requests for other tags are
+/// forwarded to the inner provider.
+///
+/// The cache is kept in a `RefCell`, so `Cached<P>` is not `Sync`, and may not
+/// be shared between threads, such as behind an `Arc<dyn Provider + Sync>`.
+#[cfg(feature = "alloc")]
+pub struct Cached<P> {
+    provider: P,
+    entries: Vec<(TypeId, Box<dyn CacheEntry>)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Provider> Cached<P> {
+    /// Wrap `provider`, without caching any values.
+    pub fn new(provider: P) -> Self {
+        Cached {
+            provider,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Cache values provided for `tag::Value<T>`.
+    pub fn cache<T>(mut self) -> Self
+    where
+        T: Clone + 'static,
+    {
+        let id = TypeId::of::<T>();
+        if !self.entries.iter().any(|(entry_id, _)| *entry_id == id) {
+            self.entries
+                .push((id, Box::new(CacheSlot::<T>(RefCell::new(None)))));
+        }
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Provider> Provider for Cached<P> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        // Entries which are still unfulfilled after serving cached values are
+        // filled in by the inner provider below, which is consulted at most
+        // once per request.
+        let mut misses = Vec::new();
+        for (_, entry) in &self.entries {
+            if entry.provide(request) {
+                misses.push(entry);
+            }
+        }
+        if !request.is_fulfilled() {
+            self.provider.provide(request);
+            for entry in misses {
+                entry.store(request);
+            }
+        }
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        self.provider.provided_tags()
+    }
+}
+
+/// Implementation detail: A type-erased cache slot for a `Cached<P>`.
+#[cfg(feature = "alloc")]
+trait CacheEntry {
+    /// Provide the cached value to `request`, returning `true` if the request
+    /// is for the cached type, but is still unfulfilled.
+    fn provide(&self, request: &mut Request<'_>) -> bool;
+
+    /// Cache the value provided to `request`, if any.
+    fn store(&self, request: &mut Request<'_>);
+}
+
+#[cfg(feature = "alloc")]
+struct CacheSlot<T>(RefCell<Option<T>>);
+
+#[cfg(feature = "alloc")]
+impl<T: Clone + 'static> CacheEntry for CacheSlot<T> {
+    fn provide(&self, request: &mut Request<'_>) -> bool {
+        match request.slot_mut::<tag::Value<T>>() {
+            Some(res @ None) => {
+                *res = self.0.borrow().clone();
+                res.is_none()
+            }
+            _ => false,
+        }
+    }
+
+    fn store(&self, request: &mut Request<'_>) {
+        if let Some(Some(value)) = request.slot_mut::<tag::Value<T>>() {
+            *self.0.borrow_mut() = Some(value.clone());
+        }
+    }
+}
+
+/// A batch of requests for several different tags, which may be fulfilled by
+/// a single pass through a provider.
+#[cfg(feature = "alloc")]
+pub struct Batch<'a> {
+    slots: Vec<Box<dyn Tagged<'a> + 'a>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Batch<'a> {
+    /// Create an empty `Batch`.
+    pub fn new() -> Self {
+        Batch { slots: Vec::new() }
+    }
+
+    /// Add a slot for a value with the given tag `I` to the batch.
+    pub fn with<I>(mut self) -> Self
+    where
+        I: Tag<'a>,
+    {
+        if !self.slots.iter().any(|slot| slot.is::<ReqTag<I>>()) {
+            self.slots.push(Box::new(ReqSlot::<'a, I>(None)));
+        }
+        self
+    }
+
+    /// Invoke `f` with a single `Request<'a>`, which may be fulfilled with
+    /// values for any of the tags in the batch.
+    pub fn fulfill<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Request<'a>),
+    {
+        f(Request::<'a>::wrap_tagged(self));
+    }
+
+    /// Take the value for the given tag `I` from the batch, if it was
+    /// provided.
+    pub fn take<I>(&mut self) -> Option<I::Type>
+    where
+        I: Tag<'a>,
+    {
+        self.slots
+            .iter_mut()
+            .find_map(|slot| slot.downcast_mut::<ReqTag<I>>())
+            .and_then(Option::take)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Default for Batch<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> private::Sealed for Batch<'a> {
+    fn is_fulfilled(&self) -> bool {
+        self.slots
+            .iter()
+            .all(|slot| private::Sealed::is_fulfilled(&**slot))
+    }
+
+    fn matches_id(&self, id: TypeId) -> bool {
+        self.slots.iter().any(|slot| slot.tag_id() == id)
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        let slot = self.slots.iter_mut().find(|slot| slot.tag_id() == id)?;
+        Some(&mut **slot as *mut (dyn Tagged<'a> + 'a) as *mut ())
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        self.slots
+            .iter()
+            .any(|slot| slot.tag_id() == id && private::Sealed::is_fulfilled(&**slot))
+    }
+
+    fn batch_slot(&mut self, id: TypeId) -> Option<*mut ()> {
+        let slot = self.slots.iter_mut().find(|slot| slot.tag_id() == id)?;
+        Some(slot as *mut Box<dyn Tagged<'a> + 'a> as *mut ())
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl<'a> Tagged<'a> for Batch<'a> {
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<BatchTag>()
+    }
+}
+
+/// Implementation detail: `Tag` which a `Batch` is tagged with.
+#[cfg(feature = "alloc")]
+struct BatchTag;
+#[cfg(feature = "alloc")]
+impl<'a> Tag<'a> for BatchTag {
+    type Type = Batch<'a>;
+}
+
+/// A request for a value with the given tag `I`, which additionally carries a
+/// runtime key of type `K`.
+///
+/// Keyed requests may only be fulfilled with [`Request::provide_keyed`] using
+/// an equal key, allowing a single provider to answer per-key lookups, such as
+/// for configuration values. They are not fulfilled by plain `provide` calls
+/// for `I`, but are still reported as requests for `I` by `Request::is` and
+/// `Request::matches_id`, so adapters such as `Filtered` treat them like any
+/// other request for `I`.
+pub struct KeyedRequest<'a, K, I>
+where
+    I: Tag<'a>,
+{
+    value: Option<I::Type>,
+    key: K,
+}
+
+impl<'a, K, I> KeyedRequest<'a, K, I>
+where
+    K: PartialEq + 'static,
+    I: Tag<'a>,
+{
+    /// Create an unfulfilled request for the value for `key`.
+    pub fn new(key: K) -> Self {
+        KeyedRequest { value: None, key }
+    }
+
+    /// The key which this request is for.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Invoke `f` with a `Request<'a>` for this keyed request.
+    pub fn fulfill<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Request<'a>),
+    {
+        f(Request::<'a>::wrap_tagged(self));
+    }
+
+    /// Take the value provided for the key, if any.
+    pub fn take(&mut self) -> Option<I::Type> {
+        self.value.take()
+    }
+}
+
+impl<'a, K, I> private::Sealed for KeyedRequest<'a, K, I>
+where
+    K: PartialEq + 'static,
+    I: Tag<'a>,
+{
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<KeyedSlot<K, I>>() {
+            Some(self as *mut Self as *mut ())
+        } else {
+            None
+        }
+    }
+
+    fn is_fulfilled(&self) -> bool {
+        self.value.is_some()
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>() && self.value.is_some()
+    }
+}
+
+unsafe impl<'a, K, I> Tagged<'a> for KeyedRequest<'a, K, I>
+where
+    K: PartialEq + 'static,
+    I: Tag<'a>,
+{
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<KeyedTag<K, I>>()
+    }
+}
+
+/// Implementation detail: `Tag` which a `KeyedRequest` is tagged with.
+struct KeyedTag<K, I>(PhantomData<(K, I)>);
+
+/// Implementation detail: Marker identifying the slot of a `KeyedRequest`,
+/// which includes its key.
+///
+/// This is distinct from `KeyedTag`, so that `Request::provide_erased` cannot
+/// swap a whole `KeyedRequest`, including its key, into the request.
+struct KeyedSlot<K, I>(PhantomData<(K, I)>);
+
+impl<'a, K, I> Tag<'a> for KeyedTag<K, I>
+where
+    K: PartialEq + 'static,
+    I: Tag<'a>,
+{
+    type Type = KeyedRequest<'a, K, I>;
+}
+
+/// Request the value with the given tag `I` for `key`. The closure argument
+/// will be invoked with a reference to a `KeyedRequest`, which may be
+/// fulfilled with `Request::provide_keyed`.
+#[must_use]
+pub fn request_keyed<'a, I, K, F>(key: K, f: F) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+    K: PartialEq + 'static,
+    F: FnOnce(&mut Request<'a>),
+{
+    let mut request = KeyedRequest::<'a, K, I>::new(key);
+    request.fulfill(f);
+    request.take()
+}
+
+/// A request for a value with the given tag `I`, which additionally carries an
+/// argument tagged with `Arg` for the provider to compute the value from.
+///
+/// Argument requests may only be fulfilled with
+/// [`Request::provide_computed_from`], turning providers into
+/// request-parameterized functions. Like `KeyedRequest`s, they are not
+/// fulfilled by plain `provide` calls for `I`, but are still reported as
+/// requests for `I`.
+pub struct ArgRequest<'a, Arg, I>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+{
+    value: Option<I::Type>,
+    arg: Arg::Type,
+}
+
+impl<'a, Arg, I> ArgRequest<'a, Arg, I>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+{
+    /// Create an unfulfilled request for the value computed from `arg`.
+    pub fn new(arg: Arg::Type) -> Self {
+        ArgRequest { value: None, arg }
+    }
+
+    /// The argument which this request carries.
+    pub fn arg(&self) -> &Arg::Type {
+        &self.arg
+    }
+
+    /// Invoke `f` with a `Request<'a>` for this argument request.
+    pub fn fulfill<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Request<'a>),
+    {
+        f(Request::<'a>::wrap_tagged(self));
+    }
+
+    /// Take the value computed from the argument, if any.
+    pub fn take(&mut self) -> Option<I::Type> {
+        self.value.take()
+    }
+}
+
+impl<'a, Arg, I> private::Sealed for ArgRequest<'a, Arg, I>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+{
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<ArgSlot<Arg, I>>() {
+            Some(self as *mut Self as *mut ())
+        } else {
+            None
+        }
+    }
+
+    fn is_fulfilled(&self) -> bool {
+        self.value.is_some()
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>() && self.value.is_some()
+    }
+}
+
+unsafe impl<'a, Arg, I> Tagged<'a> for ArgRequest<'a, Arg, I>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+{
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<ArgTag<Arg, I>>()
+    }
+}
+
+/// Implementation detail: `Tag` which an `ArgRequest` is tagged with.
+struct ArgTag<Arg, I>(PhantomData<(Arg, I)>);
+
+/// Implementation detail: Marker identifying the slot of an `ArgRequest`,
+/// which includes its argument. Like `KeyedSlot`, this is distinct from
+/// `ArgTag`.
+struct ArgSlot<Arg, I>(PhantomData<(Arg, I)>);
+
+impl<'a, Arg, I> Tag<'a> for ArgTag<Arg, I>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+{
+    type Type = ArgRequest<'a, Arg, I>;
+}
+
+/// Request the value with the given tag `I` computed from `arg`. The closure
+/// argument will be invoked with a reference to an `ArgRequest`, which may be
+/// fulfilled with `Request::provide_computed_from`.
+#[must_use]
+pub fn request_with_arg<'a, Arg, I, F>(
+    arg: <Arg as Tag<'a>>::Type,
+    f: F,
+) -> Option<<I as Tag<'a>>::Type>
+where
+    Arg: Tag<'a>,
+    I: Tag<'a>,
+    F: FnOnce(&mut Request<'a>),
+{
+    let mut request = ArgRequest::<'a, Arg, I>::new(arg);
+    request.fulfill(f);
+    request.take()
+}
+
+macro_rules! dyn_provider_impls {
+    ($($dyn:ty),*) => {
+        $(
+            impl $dyn {
+                #[must_use]
+                pub fn request<'a, I>(&'a self) -> Option<I::Type>
+                where
+                    I: Tag<'a>,
+                {
+                    request::<I, _>(|request| self.provide(request))
+                }
+
+                /// Request a value with the given tag `I` from this provider,
+                /// panicking if it is not provided.
+                ///
+                /// # Panics
+                ///
+                /// Panics with the name of the tag if the provider does not
+                /// provide a value for `I`.
+                #[track_caller]
+                pub fn request_expect<'a, I>(&'a self) -> I::Type
+                where
+                    I: Tag<'a>,
+                {
+                    match self.request::<I>() {
+                        Some(value) => value,
+                        None => panic!(
+                            "provider did not provide a value for `{}`",
+                            core::any::type_name::<I>()
+                        ),
+                    }
+                }
+
+                /// Request a `tag::Ref<T>` from this provider, falling back to
+                /// `default` if it is not provided.
+                #[must_use]
+                pub fn request_ref_or<'a, T>(&'a self, default: &'a T) -> &'a T
+                where
+                    T: ?Sized + 'static,
+                {
+                    self.request::<tag::Ref<T>>().unwrap_or(default)
+                }
+
+                /// Request a `tag::Value<T>` from this provider, falling back
+                /// to `default` if it is not provided.
+                #[must_use]
+                pub fn request_value_or<T>(&self, default: T) -> T
+                where
+                    T: 'static,
+                {
+                    self.request::<tag::Value<T>>().unwrap_or(default)
+                }
+            }
+        )*
+    };
+}
+
+dyn_provider_impls!(
+    dyn Provider,
+    dyn Provider + Send,
+    dyn Provider + Sync,
+    dyn Provider + Send + Sync
+);
+
+/// Create a type-erased `Request<'a>` for the given type tag `I`. The closure
+/// argument will be invoked with a reference to this request, which may be
+/// fulfilled dynamically.
+#[must_use]
+pub fn request<'a, I, F>(f: F) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+    F: FnOnce(&mut Request<'a>),
+{
+    let mut result: Option<<I as Tag<'a>>::Type> = None;
+    request_into::<I, F>(&mut result, f);
+    result
+}
+
+/// Create a type-erased `Request<'a>` for the given tag value `tag`, which
+/// providers may inspect with `Request::tag_data`.
+///
+/// Apart from carrying `tag`, this behaves like `request`.
+#[must_use]
+pub fn request_with_tag<'a, I, F>(tag: I, f: F) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+    F: FnOnce(&mut Request<'a>),
+{
+    let mut slot = DataReqSlot::<'a, I> { value: None, tag };
+    f(Request::<'a>::wrap_tagged(&mut slot));
+    slot.value
+}
+
+/// A flag which may be used to cooperatively cancel a request made with
+/// `request_cancellable`.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+}
+
+impl CancelToken {
+    /// Create a token which has not been cancelled.
+    pub const fn new() -> Self {
+        CancelToken {
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Cancel any requests made with this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Create a type-erased `Request<'a>` for the given type tag `I`, which may be
+/// cancelled using `token`.
+///
+/// Apart from reporting cancellation through `Request::is_cancelled`, this
+/// behaves like `request`. Cancellation is only supported for these plain
+/// requests: it cannot be combined with the other kinds of request, such as
+/// those made with a `Batch`, `KeyedRequest`, `ArgRequest` or
+/// `request_with_tag`.
+#[must_use]
+pub fn request_cancellable<'a, I, F>(token: &'a CancelToken, f: F) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+    F: FnOnce(&mut Request<'a>),
+{
+    let mut slot = CancellableReqSlot::<'a, I> { value: None, token };
+    f(Request::<'a>::wrap_tagged(&mut slot));
+    slot.value
+}
+
+/// Request a value with the given tag `I` from each of `providers` in turn,
+/// returning the first value provided.
+///
+/// Providers after the first one to fulfill the request are not consulted.
+#[must_use]
+pub fn request_first<'a, I>(providers: &'a [&'a dyn Provider]) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+{
+    providers
+        .iter()
+        .find_map(|provider| request::<I, _>(|request| provider.provide(request)))
+}
+
+/// Request a value with the given tag `I` from each of `providers` in turn,
+/// like `request_first`, but stop consulting them once `token` has been
+/// cancelled.
+#[must_use]
+pub fn request_first_cancellable<'a, I>(
+    token: &'a CancelToken,
+    providers: &'a [&'a dyn Provider],
+) -> Option<<I as Tag<'a>>::Type>
+where
+    I: Tag<'a>,
+{
+    request_cancellable::<I, _>(token, |request| {
+        for provider in providers {
+            provider.provide(request);
+            if request.is_fulfilled() || request.is_cancelled() {
+                break;
+            }
+        }
+    })
+}
+
+/// Create a `Request<'a>` for `tag::BorrowedAs<I, U>`, and invoke `with` with
+/// a borrow of the provided value, if any.
+///
+/// The owned value provided for `I` is stored in the request, and only lives
+/// until `with` returns, so the `&U` passed to `with` may not escape it.
+pub fn request_borrowed_as<'a, I, U, F, W, R>(f: F, with: W) -> R
+where
+    I: Tag<'a>,
+    I::Type: core::borrow::Borrow<U>,
+    U: ?Sized + 'static,
+    F: FnOnce(&mut Request<'a>),
+    W: FnOnce(Option<&U>) -> R,
+{
+    let storage = request::<tag::BorrowedAs<I, U>, F>(f);
+    with(storage.as_ref().map(core::borrow::Borrow::borrow))
+}
+
+/// Like `request`, but fills in the caller-provided `slot` in place, rather
+/// than returning the result by value.
+///
+/// This is a performance-oriented variant of `request`, which may avoid an
+/// extra move for large `I::Type` values. If `slot` is already `Some`, it will
+/// not be overwritten.
+pub fn request_into<'a, I, F>(slot: &mut Option<<I as Tag<'a>>::Type>, f: F)
+where
+    I: Tag<'a>,
+    F: FnOnce(&mut Request<'a>),
+{
+    // SAFETY: `ReqSlot<'a, I>` has the same representation as
+    // `Option<I::Type>` due to `#[repr(transparent)]`.
+    let slot = unsafe { &mut *(slot as *mut Option<I::Type> as *mut ReqSlot<'a, I>) };
+    f(Request::<'a>::wrap_tagged(slot));
+}
+
+/// Returns the request id for the tag `I`, which is the `TypeId` of
+/// `tag::Optional<I>`.
+///
+/// See [`Request::matches_id`] for details.
+pub fn req_tag_id<'a, I>() -> TypeId
+where
+    I: Tag<'a>,
+{
+    TypeId::of::<ReqTag<I>>()
+}
+
+/// Implementation detail: Specific `Tag` tag used by the `Request` code under
+/// the hood.
+///
+/// Composition of `Tag` types! This is just `tag::Optional`, which downstream
+/// crates may use to build their own request-like abstractions.
+type ReqTag<I> = tag::Optional<I>;
+
+/// Implementation detail: The tagged slot which a `Request` for `I` points
+/// to. Tagged with `ReqTag<I>`, but additionally tracks whether it has been
+/// fulfilled.
+#[repr(transparent)]
+struct ReqSlot<'a, I: Tag<'a>>(Option<I::Type>);
+
+impl<'a, I: Tag<'a>> private::Sealed for ReqSlot<'a, I> {
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<ReqTag<I>>() {
+            Some(self as *mut Self as *mut ())
+        } else {
+            None
+        }
+    }
+
+    fn is_fulfilled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>() && self.0.is_some()
+    }
+}
+
+unsafe impl<'a, I: Tag<'a>> Tagged<'a> for ReqSlot<'a, I> {
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<ReqTag<I>>()
+    }
+}
+
+/// Implementation detail: The tagged slot which a `Request` for `I` made by
+/// `request_with_tag` points to. Like `ReqSlot`, but additionally carries the
+/// tag value.
+struct DataReqSlot<'a, I: Tag<'a>> {
+    value: Option<I::Type>,
+    tag: I,
+}
+
+impl<'a, I: Tag<'a>> private::Sealed for DataReqSlot<'a, I> {
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<ReqTag<I>>() {
+            Some(&mut self.value as *mut Option<I::Type> as *mut ())
+        } else {
+            None
+        }
+    }
+
+    fn is_fulfilled(&self) -> bool {
+        self.value.is_some()
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>() && self.value.is_some()
+    }
+
+    fn tag_data(&self, id: TypeId) -> Option<*const ()> {
+        if id == TypeId::of::<ReqTag<I>>() {
+            Some(&self.tag as *const I as *const ())
+        } else {
+            None
+        }
+    }
+}
+
+// NOTE: Unlike `ReqSlot`, this is not tagged with `ReqTag<I>`, as it does not
+// have the representation of `Option<I::Type>`.
+unsafe impl<'a, I: Tag<'a>> Tagged<'a> for DataReqSlot<'a, I> {
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<DataReqTag<I>>()
+    }
+}
+
+/// Implementation detail: `Tag` which a `DataReqSlot` is tagged with.
+struct DataReqTag<I>(PhantomData<I>);
+
+impl<'a, I: Tag<'a>> Tag<'a> for DataReqTag<I> {
+    type Type = DataReqSlot<'a, I>;
+}
+
+/// Implementation detail: The tagged slot which a `Request` for `I` made by
+/// `request_cancellable` points to. Like `ReqSlot`, but additionally reports
+/// the state of its `CancelToken`.
+struct CancellableReqSlot<'a, I: Tag<'a>> {
+    value: Option<I::Type>,
+    token: &'a CancelToken,
+}
+
+impl<'a, I: Tag<'a>> private::Sealed for CancellableReqSlot<'a, I> {
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<ReqTag<I>>() {
+            Some(&mut self.value as *mut Option<I::Type> as *mut ())
+        } else {
+            None
+        }
+    }
+
+    fn is_fulfilled(&self) -> bool {
+        self.value.is_some()
+    }
+
+    fn is_slot_fulfilled(&self, id: TypeId) -> bool {
+        id == TypeId::of::<ReqTag<I>>() && self.value.is_some()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+// NOTE: Like `DataReqSlot`, this does not have the representation of
+// `Option<I::Type>`, so is not tagged with `ReqTag<I>`.
+unsafe impl<'a, I: Tag<'a>> Tagged<'a> for CancellableReqSlot<'a, I> {
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<CancellableReqTag<I>>()
+    }
+}
+
+/// Implementation detail: `Tag` which a `CancellableReqSlot` is tagged with.
+struct CancellableReqTag<I>(PhantomData<I>);
+
+impl<'a, I: Tag<'a>> Tag<'a> for CancellableReqTag<I> {
+    type Type = CancellableReqSlot<'a, I>;
+}
+
+// `request_into` casts `&mut Option<I::Type>` to `&mut ReqSlot<'a, I>`. Check
+// that the layouts match for a few representative tags.
+const _: () = {
+    use core::mem::{align_of, size_of};
+
+    macro_rules! assert_slot_layout {
+        ($($tag:ty),*) => {$(
+            assert!(
+                size_of::<ReqSlot<'static, $tag>>()
+                    == size_of::<Option<<$tag as Tag<'static>>::Type>>()
+            );
+            assert!(
+                align_of::<ReqSlot<'static, $tag>>()
+                    == align_of::<Option<<$tag as Tag<'static>>::Type>>()
+            );
+        )*};
+    }
+
+    assert_slot_layout!(tag::Unit, tag::Value<u64>, tag::Ref<str>, tag::RefMut<[u8]>);
+};
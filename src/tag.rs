@@ -4,11 +4,20 @@
 //! implemented by downstream crates with a `Tag<'a>` impl on the tag type.
 
 use crate::Tag;
+use core::convert::TryInto;
 use core::marker::PhantomData;
 
 /// Type-based `Tag` for `&'a T` types.
 pub struct Ref<T: ?Sized + 'static>(PhantomData<T>);
 
+impl<T: ?Sized + 'static> Ref<T> {
+    /// Creates a new `Ref` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Ref(PhantomData)
+    }
+}
+
 impl<'a, T: ?Sized + 'static> Tag<'a> for Ref<T> {
     type Type = &'a T;
 }
@@ -16,6 +25,14 @@ impl<'a, T: ?Sized + 'static> Tag<'a> for Ref<T> {
 /// Type-based `Tag` for `&'a mut T` types.
 pub struct RefMut<T: ?Sized + 'static>(PhantomData<T>);
 
+impl<T: ?Sized + 'static> RefMut<T> {
+    /// Creates a new `RefMut` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        RefMut(PhantomData)
+    }
+}
+
 impl<'a, T: ?Sized + 'static> Tag<'a> for RefMut<T> {
     type Type = &'a mut T;
 }
@@ -23,6 +40,646 @@ impl<'a, T: ?Sized + 'static> Tag<'a> for RefMut<T> {
 /// Type-based `Tag` for static `T` types.
 pub struct Value<T: 'static>(PhantomData<T>);
 
+impl<T: 'static> Value<T> {
+    /// Creates a new `Value` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Value(PhantomData)
+    }
+}
+
 impl<'a, T: 'static> Tag<'a> for Value<T> {
     type Type = T;
 }
+
+/// `Tag` combinator for optional values tagged with `I`.
+///
+/// This is the canonical primitive for composing `Tag`s into request-like
+/// abstractions: a `&mut Option<I::Type>` tagged with `Optional<I>` may be
+/// passed around as a `&mut dyn Tagged<'a>` out-parameter, and filled in by
+/// whoever downcasts it. The `provider` module is built on top of it.
+///
+/// Note that `Optional` is not idempotent: each layer of `Optional` adds a
+/// layer of `Option`, so `Optional<Optional<I>>` has a `Type` of
+/// `Option<Option<I::Type>>`. As the provider machinery wraps results in an
+/// additional `Option`, requesting an `Optional<I>` produces an
+/// `Option<Option<I::Type>>`, where the outer `Option` signals whether the
+/// request was fulfilled at all.
+pub struct Optional<I>(PhantomData<I>);
+
+impl<I: 'static> Optional<I> {
+    /// Creates a new `Optional` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Optional(PhantomData)
+    }
+}
+
+impl<'a, I: Tag<'a>> Tag<'a> for Optional<I> {
+    type Type = Option<I::Type>;
+}
+
+/// `Tag` for `Result`s of values tagged with `T` and errors tagged with `E`,
+/// for fallible provision.
+///
+/// Providers may fill this tag with [`Request::provide_ok`] or
+/// [`Request::provide_err`].
+///
+/// [`Request::provide_ok`]: crate::provider::Request::provide_ok
+/// [`Request::provide_err`]: crate::provider::Request::provide_err
+pub struct Result<T, E>(PhantomData<(T, E)>);
+
+impl<'a, T: Tag<'a>, E: Tag<'a>> Tag<'a> for Result<T, E> {
+    type Type = core::result::Result<T::Type, E::Type>;
+}
+
+/// `Tag` for boxed `FnOnce` closures producing a value tagged with `Out`.
+///
+/// The closure may borrow from the provider for `'a`, allowing computation
+/// to be deferred until the caller chooses to invoke it.
+#[cfg(feature = "alloc")]
+pub struct BoxedFnOnce<Out>(PhantomData<Out>);
+
+#[cfg(feature = "alloc")]
+impl<'a, Out: Tag<'a>> Tag<'a> for BoxedFnOnce<Out> {
+    type Type = alloc::boxed::Box<dyn FnOnce() -> Out::Type + 'a>;
+}
+
+/// `Tag` for owned values tagged with `I`, which the requester borrows as
+/// `&U` rather than taking ownership of.
+///
+/// The owned value is stored in the request itself, and is dropped once the
+/// requester is done borrowing it, so the borrow cannot outlive the request.
+/// Providers fill this tag with [`Request::provide_borrowed_as`], and requests
+/// are made with [`provider::request_borrowed_as`].
+///
+/// [`Request::provide_borrowed_as`]: crate::provider::Request::provide_borrowed_as
+/// [`provider::request_borrowed_as`]: crate::provider::request_borrowed_as
+pub struct BorrowedAs<I, U: ?Sized + 'static>(PhantomData<I>, PhantomData<U>);
+
+impl<'a, I, U> Tag<'a> for BorrowedAs<I, U>
+where
+    I: Tag<'a>,
+    I::Type: core::borrow::Borrow<U>,
+    U: ?Sized + 'static,
+{
+    type Type = I::Type;
+}
+
+/// `Tag` for borrowed `&'a dyn Fn` callbacks taking a value tagged with `Arg`
+/// and returning a value tagged with `Ret`.
+///
+/// Unlike [`BoxedFnOnce`], the callback may be invoked repeatedly, and does
+/// not require allocation.
+pub struct FnRef<Arg, Ret>(PhantomData<(Arg, Ret)>);
+
+impl<'a, Arg: Tag<'a>, Ret: Tag<'a>> Tag<'a> for FnRef<Arg, Ret> {
+    type Type = &'a (dyn Fn(Arg::Type) -> Ret::Type + 'a);
+}
+
+/// `Tag` for type-erased `Box<dyn Any>` values, for plugin boundaries where
+/// the concrete type is unknown to the requester.
+///
+/// The requester recovers the value with `Any`'s own downcasting API.
+#[cfg(feature = "alloc")]
+pub struct AnyBox;
+
+#[cfg(feature = "alloc")]
+impl<'a> Tag<'a> for AnyBox {
+    type Type = alloc::boxed::Box<dyn core::any::Any + 'static>;
+}
+
+/// `Tag` for one-shot deferred values tagged with `I`, which the requester
+/// may choose not to evaluate.
+///
+/// This has the same `Type` as `BoxedFnOnce<I>`, but is a distinct tag, so
+/// providers offering a thunk for `I` specifically can be told apart from
+/// those offering arbitrary boxed closures.
+#[cfg(feature = "alloc")]
+pub struct Lazy<I>(PhantomData<I>);
+
+#[cfg(feature = "alloc")]
+impl<'a, I: Tag<'a>> Tag<'a> for Lazy<I> {
+    type Type = alloc::boxed::Box<dyn FnOnce() -> I::Type + 'a>;
+}
+
+/// `Tag` for `&'a Dyn` references to trait objects.
+///
+/// This is equivalent to `Ref<Dyn>`, but signals that `Dyn` is intended to be
+/// a trait object type. As tags must be `'static`, the trait object must also
+/// be `'static`, e.g. `DynRef<dyn Trait>` is `DynRef<dyn Trait + 'static>`.
+pub struct DynRef<Dyn: ?Sized + 'static>(PhantomData<Dyn>);
+
+impl<Dyn: ?Sized + 'static> DynRef<Dyn> {
+    /// Creates a new `DynRef` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        DynRef(PhantomData)
+    }
+}
+
+impl<'a, Dyn: ?Sized + 'static> Tag<'a> for DynRef<Dyn> {
+    type Type = &'a Dyn;
+}
+
+/// `Tag` for `&'a mut Dyn` references to trait objects.
+///
+/// See [`DynRef`] for details.
+pub struct DynRefMut<Dyn: ?Sized + 'static>(PhantomData<Dyn>);
+
+impl<Dyn: ?Sized + 'static> DynRefMut<Dyn> {
+    /// Creates a new `DynRefMut` tag.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        DynRefMut(PhantomData)
+    }
+}
+
+impl<'a, Dyn: ?Sized + 'static> Tag<'a> for DynRefMut<Dyn> {
+    type Type = &'a mut Dyn;
+}
+
+/// `Tag` combinator for pairs of values tagged with `A` and `B`.
+pub struct Pair<A, B>(PhantomData<(A, B)>);
+
+impl<'a, A: Tag<'a>, B: Tag<'a>> Tag<'a> for Pair<A, B> {
+    type Type = (A::Type, B::Type);
+}
+
+/// `Tag` combinator for 3-tuples of values tagged with `A`, `B` and `C`.
+pub struct Tuple3<A, B, C>(PhantomData<(A, B, C)>);
+
+impl<'a, A: Tag<'a>, B: Tag<'a>, C: Tag<'a>> Tag<'a> for Tuple3<A, B, C> {
+    type Type = (A::Type, B::Type, C::Type);
+}
+
+/// `Tag` combinator for 4-tuples of values tagged with `A`, `B`, `C` and `D`.
+pub struct Tuple4<A, B, C, D>(PhantomData<(A, B, C, D)>);
+
+impl<'a, A: Tag<'a>, B: Tag<'a>, C: Tag<'a>, D: Tag<'a>> Tag<'a> for Tuple4<A, B, C, D> {
+    type Type = (A::Type, B::Type, C::Type, D::Type);
+}
+
+/// `Tag` for presence-only signals, with a `Type` of `()`.
+///
+/// A provider may provide a `Unit`-like tag to signal that it supports some
+/// capability, without providing any data.
+pub struct Unit;
+
+impl<'a> Tag<'a> for Unit {
+    type Type = ();
+}
+
+/// `Tag` for `&'a [u8]` byte slices.
+///
+/// See [`BytesExt`] for helpers to read integers from the provided slice.
+pub struct Bytes;
+
+impl<'a> Tag<'a> for Bytes {
+    type Type = &'a [u8];
+}
+
+/// Helper methods for reading integers from byte slices, such as those
+/// provided with the [`Bytes`] tag.
+///
+/// Each method returns `None` if the slice is too short to read a value at
+/// the given offset.
+pub trait BytesExt {
+    /// Read a little-endian `u32` at `offset`.
+    fn read_u32_le(&self, offset: usize) -> Option<u32>;
+    /// Read a big-endian `u32` at `offset`.
+    fn read_u32_be(&self, offset: usize) -> Option<u32>;
+    /// Read a little-endian `u64` at `offset`.
+    fn read_u64_le(&self, offset: usize) -> Option<u64>;
+    /// Read a big-endian `u64` at `offset`.
+    fn read_u64_be(&self, offset: usize) -> Option<u64>;
+}
+
+fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Option<[u8; N]> {
+    bytes.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+impl BytesExt for [u8] {
+    fn read_u32_le(&self, offset: usize) -> Option<u32> {
+        read_array(self, offset).map(u32::from_le_bytes)
+    }
+
+    fn read_u32_be(&self, offset: usize) -> Option<u32> {
+        read_array(self, offset).map(u32::from_be_bytes)
+    }
+
+    fn read_u64_le(&self, offset: usize) -> Option<u64> {
+        read_array(self, offset).map(u64::from_le_bytes)
+    }
+
+    fn read_u64_be(&self, offset: usize) -> Option<u64> {
+        read_array(self, offset).map(u64::from_be_bytes)
+    }
+}
+
+/// Type-based `Tag` for owned `Rc<T>` values.
+///
+/// This is equivalent to `Value<Rc<T>>`, but signals that the provided value
+/// has shared ownership.
+#[cfg(feature = "alloc")]
+pub struct RcType<T: ?Sized + 'static>(PhantomData<T>);
+
+#[cfg(feature = "alloc")]
+impl<'a, T: ?Sized + 'static> Tag<'a> for RcType<T> {
+    type Type = alloc::rc::Rc<T>;
+}
+
+/// Type-based `Tag` for owned `Arc<T>` values.
+///
+/// This is equivalent to `Value<Arc<T>>`, but signals that the provided value
+/// has shared ownership.
+#[cfg(feature = "alloc")]
+pub struct ArcType<T: ?Sized + 'static>(PhantomData<T>);
+
+#[cfg(feature = "alloc")]
+impl<'a, T: ?Sized + 'static> Tag<'a> for ArcType<T> {
+    type Type = alloc::sync::Arc<T>;
+}
+
+/// `Tag` for shared `Arc<Dyn>` trait object handles.
+///
+/// This is equivalent to `ArcType<Dyn>`, but signals that `Dyn` is intended to
+/// be a trait object type. See [`DynRef`] for details.
+#[cfg(feature = "alloc")]
+pub struct ArcDyn<Dyn: ?Sized + 'static>(PhantomData<Dyn>);
+
+#[cfg(feature = "alloc")]
+impl<'a, Dyn: ?Sized + 'static> Tag<'a> for ArcDyn<Dyn> {
+    type Type = alloc::sync::Arc<Dyn>;
+}
+
+/// `Tag` for zero-sized markers tied to the request lifetime `'a`, with a
+/// `Type` of `PhantomData<&'a ()>`.
+///
+/// Unlike [`Unit`], the provided marker cannot outlive the provider it was
+/// obtained from, which makes it suitable for lifetime-bound capability tokens.
+pub struct PhantomLifetime;
+
+impl<'a> Tag<'a> for PhantomLifetime {
+    type Type = PhantomData<&'a ()>;
+}
+
+/// `Tag` for boxed iterators over values tagged with `I`.
+///
+/// The iterator may borrow from the provider for `'a`, allowing results to be
+/// streamed lazily rather than collected.
+#[cfg(feature = "alloc")]
+pub struct Iter<I>(PhantomData<I>);
+
+#[cfg(feature = "alloc")]
+impl<'a, I: Tag<'a>> Tag<'a> for Iter<I> {
+    type Type = alloc::boxed::Box<dyn Iterator<Item = I::Type> + 'a>;
+}
+
+/// `Tag` for pinned, boxed futures resolving to values tagged with `Out`.
+///
+/// The future may borrow from the provider for `'a`, allowing providers to
+/// hand out asynchronous computations.
+#[cfg(feature = "alloc")]
+pub struct PinBoxFuture<Out>(PhantomData<Out>);
+
+#[cfg(feature = "alloc")]
+impl<'a, Out: Tag<'a>> Tag<'a> for PinBoxFuture<Out> {
+    type Type =
+        core::pin::Pin<alloc::boxed::Box<dyn core::future::Future<Output = Out::Type> + 'a>>;
+}
+
+/// Type-based `Tag` for explicitly `'static` values.
+///
+/// This behaves identically to [`Value`], but signals that the provided value
+/// never borrows from the provider, even in APIs which mix borrowed and
+/// static tags.
+///
+/// ```compile_fail
+/// use dyno::{provider, tag};
+///
+/// fn borrowed<'a>(value: &'a str) -> Option<&'a str> {
+///     provider::request::<tag::Static<&'a str>, _>(|request| {
+///         request.provide::<tag::Static<&'a str>>(value);
+///     })
+/// }
+/// ```
+pub struct Static<T: 'static>(PhantomData<T>);
+
+impl<'a, T> Tag<'a> for Static<T>
+where
+    T: 'static,
+{
+    type Type = T;
+}
+
+/// `Tag` for `core::time::Duration` values, equivalent to `Value<Duration>`.
+pub struct DurationValue;
+
+impl<'a> Tag<'a> for DurationValue {
+    type Type = core::time::Duration;
+}
+
+/// Type-based `Tag` for `core::ops::Range<T>` values.
+pub struct Range<T: 'static>(PhantomData<T>);
+
+impl<'a, T: 'static> Tag<'a> for Range<T> {
+    type Type = core::ops::Range<T>;
+}
+
+/// Type-based `Tag` for `core::ops::RangeInclusive<T>` values.
+pub struct RangeInclusive<T: 'static>(PhantomData<T>);
+
+impl<'a, T: 'static> Tag<'a> for RangeInclusive<T> {
+    type Type = core::ops::RangeInclusive<T>;
+}
+
+/// `Tag` for `&'a str` string slices, equivalent to `Ref<str>`.
+pub struct Str;
+
+impl<'a> Tag<'a> for Str {
+    type Type = &'a str;
+}
+
+/// `Tag` for `&'a core::ffi::CStr` C string slices, for providers bridging C
+/// APIs.
+pub struct CStr;
+
+impl<'a> Tag<'a> for CStr {
+    type Type = &'a core::ffi::CStr;
+}
+
+/// `Tag` for borrowed `&'a BTreeMap<K, V>` views, equivalent to
+/// `Ref<BTreeMap<K, V>>`.
+#[cfg(feature = "alloc")]
+pub struct MapRef<K: 'static, V: 'static>(PhantomData<(K, V)>);
+
+#[cfg(feature = "alloc")]
+impl<'a, K: 'static, V: 'static> Tag<'a> for MapRef<K, V> {
+    type Type = &'a alloc::collections::BTreeMap<K, V>;
+}
+
+/// `Tag` for borrowed `&'a HashMap<K, V>` views, equivalent to
+/// `Ref<HashMap<K, V>>`.
+#[cfg(feature = "std")]
+pub struct HashMapRef<K: 'static, V: 'static>(PhantomData<(K, V)>);
+
+#[cfg(feature = "std")]
+impl<'a, K: 'static, V: 'static> Tag<'a> for HashMapRef<K, V> {
+    type Type = &'a std::collections::HashMap<K, V>;
+}
+
+/// `Tag` for `&'a dyn Display` trait objects, allowing a provider to expose a
+/// renderable view of its state without committing to a concrete type.
+pub struct DisplayRef;
+
+impl<'a> Tag<'a> for DisplayRef {
+    type Type = &'a dyn core::fmt::Display;
+}
+
+/// `Tag` for `&'a dyn Debug` trait objects.
+pub struct DebugRef;
+
+impl<'a> Tag<'a> for DebugRef {
+    type Type = &'a dyn core::fmt::Debug;
+}
+
+/// Type-based `Tag` for `*const T` raw pointers.
+///
+/// Raw pointers carry no lifetime, so nothing ties the pointee to the provider
+/// it was obtained from. Requesters are responsible for upholding the validity
+/// and provenance requirements documented by the provider before
+/// dereferencing the pointer.
+pub struct RawConstPtr<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for RawConstPtr<T> {
+    type Type = *const T;
+}
+
+/// Type-based `Tag` for `*mut T` raw pointers.
+///
+/// See [`RawConstPtr`] for the safety implications of providing raw pointers.
+pub struct RawMutPtr<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for RawMutPtr<T> {
+    type Type = *mut T;
+}
+
+/// `Tag` for pointer-like values tagged with `I`, where absence is
+/// represented by the type's own null value, such as a null raw pointer.
+///
+/// Unlike [`Optional`], this does not wrap the value in an outer `Option`,
+/// which would be redundant for pointers crossing an FFI boundary. Providing a
+/// null value still fulfills the request, so a null pointer tells the
+/// requester that the provider explicitly has no value.
+///
+/// `I` must be a raw pointer tag, such as [`RawConstPtr`] or [`RawMutPtr`]:
+///
+/// ```compile_fail
+/// use dyno::{provider, tag};
+///
+/// // `u32` has no null value.
+/// let _ = provider::request::<tag::Nullable<tag::Value<u32>>, _>(|_| {});
+/// ```
+pub struct Nullable<I: nullable::Sealed>(PhantomData<I>);
+
+impl<'a, I: Tag<'a> + nullable::Sealed> Tag<'a> for Nullable<I> {
+    type Type = I::Type;
+}
+
+mod nullable {
+    use super::{RawConstPtr, RawMutPtr};
+
+    /// Implementation detail: Implemented for the tags of types with a null
+    /// value.
+    pub trait Sealed {}
+
+    impl<T: ?Sized + 'static> Sealed for RawConstPtr<T> {}
+    impl<T: ?Sized + 'static> Sealed for RawMutPtr<T> {}
+}
+
+/// Type-based `Tag` for `core::ptr::NonNull<T>` pointers.
+///
+/// See [`RawConstPtr`] for the safety implications of providing raw pointers.
+pub struct NonNullPtr<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for NonNullPtr<T> {
+    type Type = core::ptr::NonNull<T>;
+}
+
+/// Type-based `Tag` for `SmallVec<[T; N]>` values, allowing providers to
+/// return small collections without heap allocation.
+#[cfg(feature = "smallvec")]
+pub struct SmallVecValue<T: 'static, const N: usize>(PhantomData<T>);
+
+#[cfg(feature = "smallvec")]
+impl<'a, T: 'static, const N: usize> Tag<'a> for SmallVecValue<T, N> {
+    type Type = smallvec::SmallVec<[T; N]>;
+}
+
+/// Type-based `Tag` for owned clones of values which the provider holds by
+/// reference.
+///
+/// Providers should fill this tag with [`Request::provide_owned`], which only
+/// clones the value if it is requested.
+///
+/// [`Request::provide_owned`]: crate::provider::Request::provide_owned
+pub struct Owned<T: Clone + 'static>(PhantomData<T>);
+
+impl<'a, T: Clone + 'static> Tag<'a> for Owned<T> {
+    type Type = T;
+}
+
+/// Type-based `Tag` for owned `Vec<T>` values.
+///
+/// This is equivalent to `Value<Vec<T>>`, but signals that the provided value
+/// is a collection which the requester takes ownership of.
+#[cfg(feature = "alloc")]
+pub struct VecValue<T: 'static>(PhantomData<T>);
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'static> Tag<'a> for VecValue<T> {
+    type Type = alloc::vec::Vec<T>;
+}
+
+/// `Tag` for borrowed `&'a Path` values, equivalent to `Ref<Path>`.
+#[cfg(feature = "std")]
+pub struct PathRef;
+
+#[cfg(feature = "std")]
+impl<'a> Tag<'a> for PathRef {
+    type Type = &'a std::path::Path;
+}
+
+/// `Tag` for owned `PathBuf` values, equivalent to `Value<PathBuf>`.
+#[cfg(feature = "std")]
+pub struct PathBufValue;
+
+#[cfg(feature = "std")]
+impl<'a> Tag<'a> for PathBufValue {
+    type Type = std::path::PathBuf;
+}
+
+/// `Tag` for live `RwLockReadGuard<'a, T>` guards, allowing providers holding
+/// an `RwLock` to hand out shared access to its contents.
+#[cfg(feature = "std")]
+pub struct ReadGuard<T: ?Sized + 'static>(PhantomData<T>);
+
+#[cfg(feature = "std")]
+impl<'a, T: ?Sized + 'static> Tag<'a> for ReadGuard<T> {
+    type Type = std::sync::RwLockReadGuard<'a, T>;
+}
+
+/// `Tag` for live `RwLockWriteGuard<'a, T>` guards.
+///
+/// See [`ReadGuard`] for details.
+#[cfg(feature = "std")]
+pub struct WriteGuard<T: ?Sized + 'static>(PhantomData<T>);
+
+#[cfg(feature = "std")]
+impl<'a, T: ?Sized + 'static> Tag<'a> for WriteGuard<T> {
+    type Type = std::sync::RwLockWriteGuard<'a, T>;
+}
+
+/// `Tag` for borrowed `&'a Cell<T>` values, allowing requesters to mutate
+/// provider state within `'a`.
+pub struct CellRef<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for CellRef<T> {
+    type Type = &'a core::cell::Cell<T>;
+}
+
+/// `Tag` for borrowed `&'a RefCell<T>` values.
+///
+/// See [`CellRef`] for details.
+pub struct RefCellRef<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for RefCellRef<T> {
+    type Type = &'a core::cell::RefCell<T>;
+}
+
+/// `Tag` for `&'a &'a T` nested references, for interoperating with APIs
+/// which hold references to references.
+pub struct RefRef<T: ?Sized + 'static>(PhantomData<T>);
+
+impl<'a, T: ?Sized + 'static> Tag<'a> for RefRef<T> {
+    type Type = &'a &'a T;
+}
+
+/// `Tag` for `Cow<'a, str>` values, which may either borrow from the provider
+/// or be owned.
+#[cfg(feature = "alloc")]
+pub struct CowStr;
+
+#[cfg(feature = "alloc")]
+impl<'a> Tag<'a> for CowStr {
+    type Type = alloc::borrow::Cow<'a, str>;
+}
+
+/// `Tag` for `Cow<'a, [T]>` values, which may either borrow from the provider
+/// or be owned.
+#[cfg(feature = "alloc")]
+pub struct CowSlice<T: Clone + 'static>(PhantomData<T>);
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone + 'static> Tag<'a> for CowSlice<T> {
+    type Type = alloc::borrow::Cow<'a, [T]>;
+}
+
+/// Type-based `Tag` for `core::num::Wrapping<T>` values.
+pub struct WrappingValue<T: 'static>(PhantomData<T>);
+
+impl<'a, T: 'static> Tag<'a> for WrappingValue<T> {
+    type Type = core::num::Wrapping<T>;
+}
+
+/// Type-based `Tag` for the `core::num::NonZero*` family of integers, such as
+/// `NonZeroValue<NonZeroU32>`.
+///
+/// This is equivalent to `Value<T>`, but restricted to non-zero integer types,
+/// so that the invariant is carried through the provider.
+pub struct NonZeroValue<T: nonzero::Sealed + 'static>(PhantomData<T>);
+
+impl<'a, T: nonzero::Sealed + 'static> Tag<'a> for NonZeroValue<T> {
+    type Type = T;
+}
+
+mod nonzero {
+    use core::num::*;
+
+    /// Implementation detail: Implemented for the `NonZero*` integer types.
+    pub trait Sealed {}
+
+    macro_rules! impl_sealed {
+        ($($ty:ty),*) => {$(impl Sealed for $ty {})*};
+    }
+
+    impl_sealed!(
+        NonZeroU8,
+        NonZeroU16,
+        NonZeroU32,
+        NonZeroU64,
+        NonZeroU128,
+        NonZeroUsize,
+        NonZeroI8,
+        NonZeroI16,
+        NonZeroI32,
+        NonZeroI64,
+        NonZeroI128,
+        NonZeroIsize
+    );
+}
+
+/// `Tag` for `&'a T` references to plain-old-data types, which providers may
+/// reinterpret from borrowed bytes for zero-copy parsing.
+///
+/// Providers fill this tag with [`Request::provide_pod_bytes`].
+///
+/// [`Request::provide_pod_bytes`]: crate::provider::Request::provide_pod_bytes
+#[cfg(feature = "bytemuck")]
+pub struct Pod<T: bytemuck::Pod>(PhantomData<T>);
+
+#[cfg(feature = "bytemuck")]
+impl<'a, T: bytemuck::Pod> Tag<'a> for Pod<T> {
+    type Type = &'a T;
+}
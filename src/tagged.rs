@@ -0,0 +1,179 @@
+use crate::{private, tag, Tag, Tagged};
+use core::any::TypeId;
+use core::hash::{Hash, Hasher};
+
+/// A value of type `I::Type`, tagged with the `Tag` `I`.
+///
+/// This type has the same representation as `I::Type`, and encodes the
+/// specific `Tag` implementation which a value is associated with into the
+/// type, such that when it is type erased into a `dyn Tagged<'a>`, that
+/// information is preserved.
+#[repr(transparent)]
+pub struct TagValue<'a, I>(pub I::Type)
+where
+    I: Tag<'a>;
+
+impl<'a, I> TagValue<'a, I>
+where
+    I: Tag<'a>,
+{
+    /// Tag `value` with the tag `I`.
+    ///
+    /// `From<I::Type>` cannot be implemented for `TagValue<'a, I>`, as it
+    /// would overlap with the blanket `From<T> for T` impl, so this and
+    /// [`TagValue::into_inner`] are provided instead.
+    pub fn new(value: I::Type) -> Self {
+        TagValue(value)
+    }
+
+    /// Unwraps the tagged value.
+    pub fn into_inner(self) -> I::Type {
+        self.0
+    }
+
+    /// Maps a `TagValue<'a, I>` to a `TagValue<'a, J>` by applying `f` to the
+    /// inner value.
+    pub fn map<J, F>(self, f: F) -> TagValue<'a, J>
+    where
+        J: Tag<'a>,
+        F: FnOnce(I::Type) -> J::Type,
+    {
+        TagValue(f(self.0))
+    }
+
+    /// Re-tags the value with the tag `J`, which has the same `Type` as `I`.
+    pub fn cast<J>(self) -> TagValue<'a, J>
+    where
+        J: Tag<'a, Type = I::Type>,
+    {
+        TagValue(self.0)
+    }
+}
+
+/// Projections to the components of tuple tags.
+macro_rules! tuple_projections {
+    ($($tag:ident<$($param:ident),+> { $($name:ident: $idx:tt => $ty:ident),+ })*) => {$(
+        impl<'a, $($param: Tag<'a>),+> TagValue<'a, tag::$tag<$($param),+>> {
+            $(
+                #[doc = concat!("Returns a reference to the component tagged with `", stringify!($ty), "`.")]
+                pub fn $name(&self) -> &$ty::Type {
+                    &(self.0).$idx
+                }
+            )+
+        }
+    )*};
+}
+
+tuple_projections! {
+    Pair<A, B> { first: 0 => A, second: 1 => B }
+    Tuple3<A, B, C> { first: 0 => A, second: 1 => B, third: 2 => C }
+    Tuple4<A, B, C, D> { first: 0 => A, second: 1 => B, third: 2 => C, fourth: 3 => D }
+}
+
+impl<'a, I> private::Sealed for TagValue<'a, I>
+where
+    I: Tag<'a>,
+{
+    fn matches_id(&self, id: TypeId) -> bool {
+        id == TypeId::of::<I>()
+    }
+
+    fn slot_mut(&mut self, id: TypeId) -> Option<*mut ()> {
+        if id == TypeId::of::<I>() {
+            Some(self as *mut Self as *mut ())
+        } else {
+            None
+        }
+    }
+}
+
+unsafe impl<'a, I> Tagged<'a> for TagValue<'a, I>
+where
+    I: Tag<'a>,
+{
+    // NOTE: This intentionally doesn't use an associated `const` on `Tag`.
+    // `TypeId::of` is already folded to a constant, and as `Tag` is a safe
+    // trait, an overridable id could be used to break the safety of downcasts.
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<I>()
+    }
+}
+
+impl<'a, I> AsRef<I::Type> for TagValue<'a, I>
+where
+    I: Tag<'a>,
+{
+    fn as_ref(&self) -> &I::Type {
+        &self.0
+    }
+}
+
+impl<'a, I> AsMut<I::Type> for TagValue<'a, I>
+where
+    I: Tag<'a>,
+{
+    fn as_mut(&mut self) -> &mut I::Type {
+        &mut self.0
+    }
+}
+
+impl<'a, I> Clone for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: Clone,
+{
+    fn clone(&self) -> Self {
+        TagValue(self.0.clone())
+    }
+}
+
+impl<'a, I> Default for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: Default,
+{
+    fn default() -> Self {
+        TagValue(I::Type::default())
+    }
+}
+
+impl<'a, I> PartialEq for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'a, I> Eq for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: Eq,
+{
+}
+
+impl<'a, I> Hash for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, I> serde::Serialize for TagValue<'a, I>
+where
+    I: Tag<'a>,
+    I::Type: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
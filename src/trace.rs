@@ -0,0 +1,50 @@
+//! Tracing hooks for debugging provider graphs, enabled by the `trace`
+//! feature.
+//!
+//! When a hook is installed with [`set_hook`], an [`Event`] is emitted each
+//! time a provider checks for or provides a tag, recording whether the request
+//! was for that tag. This includes checks by request id, such as with
+//! `Request::matches_any`, and provisions to keyed and argument requests. This
+//! gives visibility into why a request went unfulfilled.
+
+use core::any::TypeId;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A record of a provider checking for or providing a tag.
+#[derive(Clone, Copy, Debug)]
+pub struct Event {
+    /// The request id of the tag, as returned by `provider::req_tag_id`.
+    pub id: TypeId,
+    /// The name of the tag's type, for display purposes, or [`UNKNOWN_TAG`]
+    /// if the tag was only identified by its request id.
+    pub tag_name: &'static str,
+    /// Whether the request was for the tag.
+    pub matched: bool,
+}
+
+/// The `Event::tag_name` of tags which were only identified by their request
+/// id, such as with `Request::matches_id`.
+pub const UNKNOWN_TAG: &str = "<unknown>";
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Install `hook` to be called with each traced `Event`, replacing any
+/// previously installed hook, or remove the hook if `hook` is `None`.
+pub fn set_hook(hook: Option<fn(&Event)>) {
+    let ptr = hook.map_or(core::ptr::null_mut(), |hook| hook as *mut ());
+    HOOK.store(ptr, Ordering::Release);
+}
+
+pub(crate) fn emit(id: TypeId, tag_name: &'static str, matched: bool) {
+    let ptr = HOOK.load(Ordering::Acquire);
+    if !ptr.is_null() {
+        // SAFETY: Non-null values of `HOOK` are only stored by `set_hook`,
+        // from a `fn(&Event)`.
+        let hook = unsafe { core::mem::transmute::<*mut (), fn(&Event)>(ptr) };
+        hook(&Event {
+            id,
+            tag_name,
+            matched,
+        });
+    }
+}
@@ -60,3 +60,63 @@ fn use_object_provider() {
 
     assert_eq!(result, Some("hello, jane!"));
 }
+
+#[test]
+fn tag_box_send() {
+    fn assert_send<T: Send + ?Sized>(_: &T) {}
+
+    let tagged = <dyn Tagged>::tag_box_send::<dyno::tag::Value<u32>>(Box::new(5));
+    assert_send(&*tagged);
+
+    assert!(tagged.is::<dyno::tag::Value<u32>>());
+    assert_eq!(tagged.downcast_ref::<dyno::tag::Value<u32>>(), Some(&5));
+
+    // A failed downcast hands back the box without losing the `Send` bound.
+    let tagged = tagged
+        .downcast_box::<dyno::tag::Value<u64>>()
+        .err()
+        .unwrap();
+    assert_send(&*tagged);
+    assert_eq!(
+        *tagged.downcast_box::<dyno::tag::Value<u32>>().ok().unwrap(),
+        5
+    );
+}
+
+#[test]
+fn send_sync_tagged_methods() {
+    let value = dyno::TagValue::<dyno::tag::Value<u32>>(5);
+    let tagged: &(dyn Tagged + Send + Sync) = &value;
+    assert!(tagged.is::<dyno::tag::Value<u32>>());
+    assert_eq!(tagged.downcast_ref::<dyno::tag::Value<u32>>(), Some(&5));
+}
+
+trait MiniProvider {
+    fn provide<'a>(&'a self, out: &mut (dyn Tagged<'a> + 'a));
+}
+
+impl MiniProvider for MyType {
+    fn provide<'a>(&'a self, out: &mut (dyn Tagged<'a> + 'a)) {
+        if let Some(slot @ None) = out.downcast_mut::<dyno::tag::Optional<dyno::tag::Ref<str>>>() {
+            *slot = Some(&self.field[..]);
+        }
+    }
+}
+
+fn mini_request<'a, I: Tag<'a>>(provider: &'a dyn MiniProvider) -> Option<I::Type> {
+    let mut result = None;
+    provider.provide(<dyn Tagged>::tag_mut::<dyno::tag::Optional<I>>(&mut result));
+    result
+}
+
+#[test]
+fn use_optional_composition() {
+    let provider = MyType {
+        field: "hello, composition!".to_owned(),
+    };
+    assert_eq!(
+        mini_request::<dyno::tag::Ref<str>>(&provider),
+        Some("hello, composition!")
+    );
+    assert_eq!(mini_request::<dyno::tag::Value<u32>>(&provider), None);
+}
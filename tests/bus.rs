@@ -0,0 +1,19 @@
+use dyno::bus::Bus;
+use dyno::tag;
+use std::cell::RefCell;
+
+#[test]
+fn publish_to_subscribers() {
+    let owned = String::from("event");
+    let log = RefCell::new(Vec::new());
+    let mut bus = Bus::new();
+    bus.subscribe::<tag::Ref<str>, _>(|text| log.borrow_mut().push(format!("first: {}", text)))
+        .subscribe::<tag::Ref<str>, _>(|text| log.borrow_mut().push(format!("second: {}", text)))
+        .subscribe::<tag::Value<u32>, _>(|n| log.borrow_mut().push(format!("number: {}", n)));
+
+    bus.publish::<tag::Ref<str>>(&owned);
+    bus.publish::<tag::Value<u8>>(1);
+    drop(bus);
+
+    assert_eq!(*log.borrow(), ["first: event", "second: event"]);
+}
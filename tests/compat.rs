@@ -0,0 +1,15 @@
+use dyno::{compat, tag, Tagged};
+use std::any::Any;
+
+#[test]
+fn any_round_trip() {
+    let any: Box<dyn Any> = Box::new(5u64);
+    let value = *any.downcast::<u64>().unwrap();
+
+    let tagged: Box<dyn Tagged<'static>> = Box::new(compat::from_any(value));
+    assert!(tagged.is::<tag::Value<u64>>());
+
+    let tagged = compat::into_any::<u32>(tagged).unwrap_err();
+    let any = compat::into_any::<u64>(tagged).ok().unwrap();
+    assert_eq!(any.downcast_ref::<u64>(), Some(&5));
+}
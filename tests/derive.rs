@@ -0,0 +1,80 @@
+#![cfg(feature = "derive")]
+
+use dyno::provider::{self, Provider, Request};
+use dyno::tag;
+use std::any::TypeId;
+
+struct Inner;
+
+impl Provider for Inner {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide::<tag::Value<u32>>(5)
+            .provide::<tag::Ref<str>>("inner");
+    }
+}
+
+#[derive(Provider)]
+struct Wrapper {
+    #[provide(ref = "tag::Ref<str>")]
+    name: String,
+    #[provide(ref = "tag::Ref<[u8]>")]
+    bytes: Vec<u8>,
+    #[provide(delegate)]
+    inner: Inner,
+}
+
+#[derive(Provider)]
+struct Newtype(#[provide(delegate)] Wrapper);
+
+#[test]
+fn derived_round_trip() {
+    let wrapper = Wrapper {
+        name: "wrapper".to_owned(),
+        bytes: vec![1, 2],
+        inner: Inner,
+    };
+    let provider: Box<dyn Provider> = Box::new(Newtype(wrapper));
+    assert_eq!(provider.request::<tag::Ref<str>>(), Some("wrapper"));
+    assert_eq!(provider.request::<tag::Ref<[u8]>>(), Some(&[1, 2][..]));
+    assert_eq!(provider.request::<tag::Value<u32>>(), Some(5));
+    assert_eq!(provider.request::<tag::Value<i32>>(), None);
+}
+
+struct Advertised;
+
+impl Provider for Advertised {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide::<tag::Value<u32>>(5);
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        vec![provider::req_tag_id::<tag::Value<u32>>()]
+    }
+}
+
+#[derive(Provider)]
+struct AdvertisedWrapper {
+    #[provide(ref = "tag::Ref<str>")]
+    name: String,
+    #[provide(delegate)]
+    inner: Advertised,
+    #[provide(ref = "tag::Ref<str>")]
+    alias: String,
+}
+
+#[test]
+fn derived_provided_tags() {
+    let wrapper = AdvertisedWrapper {
+        name: "name".to_owned(),
+        inner: Advertised,
+        alias: "alias".to_owned(),
+    };
+    assert_eq!(
+        wrapper.provided_tags(),
+        [
+            provider::req_tag_id::<tag::Ref<str>>(),
+            provider::req_tag_id::<tag::Value<u32>>(),
+        ]
+    );
+}
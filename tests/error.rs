@@ -0,0 +1,43 @@
+#![cfg(feature = "error-provide")]
+#![feature(error_generic_member_access)]
+
+use dyno::{provider, tag};
+use std::error::{Error, Request};
+use std::fmt;
+
+#[derive(Debug)]
+struct Trace(Vec<&'static str>);
+
+#[derive(Debug)]
+struct MyError {
+    trace: Trace,
+}
+
+impl fmt::Display for MyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("my error")
+    }
+}
+
+impl Error for MyError {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref::<Trace>(&self.trace);
+    }
+}
+
+#[test]
+fn request_from_error() {
+    let error: Box<dyn Error> = Box::new(MyError {
+        trace: Trace(vec!["main", "run"]),
+    });
+
+    let trace = provider::request::<tag::Ref<Trace>, _>(|request| {
+        dyno::error::provide_ref::<Trace>(&*error, request)
+    });
+    assert_eq!(trace.unwrap().0, ["main", "run"]);
+
+    let missing = provider::request::<tag::Ref<str>, _>(|request| {
+        dyno::error::provide_ref::<str>(&*error, request)
+    });
+    assert!(missing.is_none());
+}
@@ -0,0 +1,63 @@
+use dyno::provider::Request;
+use dyno::{tag, Tag, TagValue, Tagged};
+use std::fmt::Debug;
+use std::mem::{align_of, size_of};
+
+macro_rules! assert_layout {
+    ($($tag:ty),* $(,)?) => {
+        const _: () = {$(
+            assert!(
+                size_of::<TagValue<'static, $tag>>()
+                    == size_of::<<$tag as Tag<'static>>::Type>()
+            );
+            assert!(
+                align_of::<TagValue<'static, $tag>>()
+                    == align_of::<<$tag as Tag<'static>>::Type>()
+            );
+        )*};
+
+        #[test]
+        fn tag_value_layout() {
+            $(check_layout::<$tag>();)*
+        }
+    };
+}
+
+fn check_layout<I: Tag<'static>>() {
+    assert_eq!(size_of::<TagValue<'static, I>>(), size_of::<I::Type>());
+    assert_eq!(align_of::<TagValue<'static, I>>(), align_of::<I::Type>());
+}
+
+assert_layout!(
+    tag::Unit,
+    tag::Value<u8>,
+    tag::Value<u64>,
+    tag::Value<String>,
+    tag::Ref<str>,
+    tag::RefMut<[u8]>,
+    tag::DynRef<dyn Debug>,
+    tag::Optional<tag::Value<u32>>,
+    tag::Tuple3<tag::Value<u8>, tag::Ref<str>, tag::Value<u64>>,
+    tag::BoxedFnOnce<tag::Value<u32>>,
+);
+
+// `Request::new` reinterprets `&mut dyn Tagged` as `&mut Request`.
+const _: () = {
+    assert!(size_of::<&mut Request<'static>>() == size_of::<&mut dyn Tagged<'static>>());
+    assert!(align_of::<&mut Request<'static>>() == align_of::<&mut dyn Tagged<'static>>());
+};
+
+#[test]
+fn request_from_tagged() {
+    let mut slot: Option<&str> = None;
+    let request = Request::new(<dyn Tagged>::tag_mut::<tag::Optional<tag::Ref<str>>>(
+        &mut slot,
+    ));
+    assert!(request.is::<tag::Ref<str>>());
+    assert!(!request.is::<tag::Value<u32>>());
+    request
+        .provide::<tag::Value<u32>>(5)
+        .provide::<tag::Ref<str>>("manual")
+        .provide::<tag::Ref<str>>("ignored");
+    assert_eq!(slot, Some("manual"));
+}
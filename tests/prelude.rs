@@ -0,0 +1,28 @@
+use dyno::prelude::*;
+
+struct Config {
+    name: String,
+    retries: u32,
+}
+
+impl Provider for Config {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide::<Ref<str>>(&self.name)
+            .provide::<Value<u32>>(self.retries);
+    }
+}
+
+#[test]
+fn provider_from_prelude() {
+    let config: Box<dyn Provider> = Box::new(Config {
+        name: "config".to_owned(),
+        retries: 3,
+    });
+    assert_eq!(config.request::<Ref<str>>(), Some("config"));
+    assert_eq!(config.request::<Value<u32>>(), Some(3));
+    assert_eq!(config.request::<RefMut<str>>(), None);
+
+    let tagged: &dyn Tagged = &TagValue::<Optional<Value<u32>>>(None);
+    assert!(tagged.is::<Optional<Value<u32>>>());
+}
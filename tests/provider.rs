@@ -0,0 +1,1284 @@
+use dyno::provider::{self, Provider, Request};
+use dyno::{tag, Tagged};
+use std::any::TypeId;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+struct Plugin {
+    field: String,
+}
+
+impl Provider for Plugin {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_with::<tag::BoxedFnOnce<tag::Value<usize>>, _>(|| {
+            Box::new(move || self.field.len())
+        });
+    }
+}
+
+#[test]
+fn provide_boxed_fn_once() {
+    let plugin: Box<dyn Provider> = Box::new(Plugin {
+        field: "deferred".to_owned(),
+    });
+
+    let f = plugin
+        .request::<tag::BoxedFnOnce<tag::Value<usize>>>()
+        .unwrap();
+    assert_eq!(f(), 8);
+    assert!(plugin.request::<tag::Value<usize>>().is_none());
+}
+
+struct Greeting;
+
+impl Provider for Greeting {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_with::<tag::Value<String>, _>(|| "hello".to_owned());
+    }
+}
+
+struct Brackets<P>(P);
+
+impl<P: Provider> Provider for Brackets<P> {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        self.0.provide(request);
+        request.with_current::<tag::Value<String>, _>(|current| {
+            current.map(|value| format!("[{}]", value))
+        });
+    }
+}
+
+#[test]
+fn with_current_decorates() {
+    let provider: Box<dyn Provider> = Box::new(Brackets(Greeting));
+    assert_eq!(
+        provider.request::<tag::Value<String>>().as_deref(),
+        Some("[hello]")
+    );
+}
+
+#[test]
+fn optional_provider() {
+    let some: Box<dyn Provider> = Box::new(Some(Greeting));
+    assert_eq!(
+        some.request::<tag::Value<String>>().as_deref(),
+        Some("hello")
+    );
+
+    let none: Box<dyn Provider> = Box::new(None::<Greeting>);
+    assert!(none.request::<tag::Value<String>>().is_none());
+}
+
+#[test]
+fn request_into_slot() {
+    let provider: Box<dyn Provider> = Box::new(Greeting);
+
+    let mut slot = None;
+    provider::request_into::<tag::Value<String>, _>(&mut slot, |request| provider.provide(request));
+    assert_eq!(slot.as_deref(), Some("hello"));
+
+    let mut filled = Some("existing".to_owned());
+    provider::request_into::<tag::Value<String>, _>(&mut filled, |request| {
+        provider.provide(request)
+    });
+    assert_eq!(filled.as_deref(), Some("existing"));
+}
+
+/// Counts the clones and drops of a value, to check that it is moved into the
+/// caller's slot without any intermediate copies.
+struct Tracked {
+    clones: Rc<Cell<u32>>,
+    drops: Rc<Cell<u32>>,
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        self.clones.set(self.clones.get() + 1);
+        Tracked {
+            clones: self.clones.clone(),
+            drops: self.drops.clone(),
+        }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+#[test]
+fn request_into_moves_into_slot() {
+    let clones = Rc::new(Cell::new(0));
+    let drops = Rc::new(Cell::new(0));
+
+    let mut slot = None;
+    provider::request_into::<tag::Value<Tracked>, _>(&mut slot, |request| {
+        request.provide::<tag::Value<Tracked>>(Tracked {
+            clones: clones.clone(),
+            drops: drops.clone(),
+        });
+    });
+    assert!(slot.is_some());
+    assert_eq!(clones.get(), 0);
+    assert_eq!(drops.get(), 0);
+
+    drop(slot);
+    assert_eq!(clones.get(), 0);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn map_tag() {
+    let provider: Box<dyn Provider> = Box::new(provider::MapTag::<
+        _,
+        tag::Value<String>,
+        tag::Value<usize>,
+        _,
+    >::new(Greeting, |value: String| value.len()));
+    assert_eq!(provider.request::<tag::Value<usize>>(), Some(5));
+    assert!(provider.request::<tag::Value<String>>().is_none());
+}
+
+struct Capable;
+
+impl Provider for Capable {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide::<tag::Unit>(());
+    }
+}
+
+#[test]
+fn unit_capability() {
+    let capable: Box<dyn Provider> = Box::new(Capable);
+    assert_eq!(capable.request::<tag::Unit>(), Some(()));
+
+    let incapable: Box<dyn Provider> = Box::new(Greeting);
+    assert_eq!(incapable.request::<tag::Unit>(), None);
+}
+
+#[test]
+fn is_fulfilled() {
+    let value = provider::request::<tag::Value<u32>, _>(|request| {
+        assert!(!request.is_fulfilled());
+        request.provide::<tag::Value<String>>("ignored".to_owned());
+        assert!(!request.is_fulfilled());
+        request.provide::<tag::Value<u32>>(5);
+        assert!(request.is_fulfilled());
+    });
+    assert_eq!(value, Some(5));
+}
+
+#[test]
+fn send_sync_provider() {
+    let provider: Arc<dyn Provider + Send + Sync> = Arc::new(Greeting);
+    let shared = provider.clone();
+    let value = std::thread::spawn(move || shared.request::<tag::Value<String>>())
+        .join()
+        .unwrap();
+    assert_eq!(value.as_deref(), Some("hello"));
+    assert!(provider.request::<tag::Unit>().is_none());
+}
+
+struct Lazy {
+    data: String,
+    calls: Cell<u32>,
+}
+
+impl Provider for Lazy {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref_with::<str, _>(|| {
+            self.calls.set(self.calls.get() + 1);
+            &self.data[..]
+        });
+    }
+}
+
+#[test]
+fn provide_ref_with_is_lazy() {
+    let lazy = Lazy {
+        data: "lazy".to_owned(),
+        calls: Cell::new(0),
+    };
+    let provider: &dyn Provider = &lazy;
+
+    assert_eq!(provider.request::<tag::Value<u32>>(), None);
+    assert_eq!(lazy.calls.get(), 0);
+
+    assert_eq!(provider.request::<tag::Ref<str>>(), Some("lazy"));
+    assert_eq!(lazy.calls.get(), 1);
+
+    let value = provider::request::<tag::Ref<str>, _>(|request| {
+        request.provide::<tag::Ref<str>>("eager");
+        provider.provide(request);
+    });
+    assert_eq!(value, Some("eager"));
+    assert_eq!(lazy.calls.get(), 1);
+}
+
+struct Counted {
+    name: String,
+    calls: Cell<u32>,
+}
+
+impl Provider for Counted {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        self.calls.set(self.calls.get() + 1);
+        request
+            .provide::<tag::Ref<str>>(&self.name)
+            .provide::<tag::Value<usize>>(self.name.len())
+            .provide_with::<tag::Value<String>, _>(|| self.name.to_uppercase());
+    }
+}
+
+#[test]
+fn batch_single_pass() {
+    let counted = Counted {
+        name: "batch".to_owned(),
+        calls: Cell::new(0),
+    };
+
+    let mut batch = provider::Batch::new()
+        .with::<tag::Ref<str>>()
+        .with::<tag::Value<usize>>()
+        .with::<tag::Value<String>>()
+        .with::<tag::Unit>();
+    batch.fulfill(|request| {
+        assert!(request.is::<tag::Ref<str>>());
+        assert!(!request.is::<tag::Ref<[u8]>>());
+        counted.provide(request);
+        assert!(!request.is_fulfilled());
+    });
+
+    assert_eq!(counted.calls.get(), 1);
+    assert_eq!(batch.take::<tag::Ref<str>>(), Some("batch"));
+    assert_eq!(batch.take::<tag::Value<usize>>(), Some(5));
+    assert_eq!(batch.take::<tag::Value<String>>().as_deref(), Some("BATCH"));
+    assert_eq!(batch.take::<tag::Unit>(), None);
+    assert_eq!(batch.take::<tag::Ref<[u8]>>(), None);
+}
+
+#[test]
+fn try_provide() {
+    let value = provider::request::<tag::Value<String>, _>(|request| {
+        let value = request
+            .try_provide::<tag::Value<Vec<u8>>>(b"bytes".to_vec())
+            .err()
+            .unwrap();
+        let value = String::from_utf8(value).unwrap();
+        assert!(request.try_provide::<tag::Value<String>>(value).is_ok());
+        let rejected = request
+            .try_provide::<tag::Value<String>>("again".to_owned())
+            .err();
+        assert_eq!(rejected.as_deref(), Some("again"));
+    });
+    assert_eq!(value.as_deref(), Some("bytes"));
+}
+
+struct Expensive {
+    calls: Rc<Cell<u32>>,
+}
+
+impl Provider for Expensive {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        self.calls.set(self.calls.get() + 1);
+        request
+            .provide_with::<tag::Value<String>, _>(|| "expensive".to_owned())
+            .provide::<tag::Value<u32>>(5);
+    }
+}
+
+#[test]
+fn cached_provider() {
+    let calls = Rc::new(Cell::new(0));
+    let cached = provider::Cached::new(Expensive {
+        calls: calls.clone(),
+    })
+    .cache::<String>();
+    let provider: &dyn Provider = &cached;
+
+    let first = provider.request::<tag::Value<String>>();
+    let second = provider.request::<tag::Value<String>>();
+    assert_eq!(first.as_deref(), Some("expensive"));
+    assert_eq!(second.as_deref(), Some("expensive"));
+    assert_eq!(calls.get(), 1);
+
+    assert_eq!(provider.request::<tag::Value<u32>>(), Some(5));
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn cached_provider_miss() {
+    let calls = Rc::new(Cell::new(0));
+    let cached = provider::Cached::new(Expensive {
+        calls: calls.clone(),
+    })
+    .cache::<u64>()
+    .cache::<String>();
+    let provider: &dyn Provider = &cached;
+
+    // A cached tag which the inner provider does not supply only consults it
+    // once per request.
+    assert_eq!(provider.request::<tag::Value<u64>>(), None);
+    assert_eq!(calls.get(), 1);
+
+    // Values are cached when requested as part of a batch.
+    let mut batch = provider::Batch::new()
+        .with::<tag::Value<String>>()
+        .with::<tag::Value<u32>>();
+    batch.fulfill(|request| provider.provide(request));
+    assert_eq!(batch.take::<tag::Value<u32>>(), Some(5));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(
+        provider.request::<tag::Value<String>>().as_deref(),
+        Some("expensive")
+    );
+    assert_eq!(calls.get(), 2);
+}
+
+struct Shared {
+    name: String,
+    calls: Cell<u32>,
+}
+
+impl Provider for Shared {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_either::<tag::Ref<str>, tag::Value<String>, _, _>(|| {
+            self.calls.set(self.calls.get() + 1);
+            &self.name[..]
+        });
+    }
+}
+
+#[test]
+fn provide_either() {
+    let shared = Shared {
+        name: "shared".to_owned(),
+        calls: Cell::new(0),
+    };
+    let provider: &dyn Provider = &shared;
+
+    assert_eq!(provider.request::<tag::Ref<str>>(), Some("shared"));
+    assert_eq!(
+        provider.request::<tag::Value<String>>().as_deref(),
+        Some("shared")
+    );
+    assert_eq!(provider.request::<tag::Value<u32>>(), None);
+    assert_eq!(shared.calls.get(), 2);
+}
+
+struct Token;
+
+impl Provider for Token {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide::<tag::PhantomLifetime>(std::marker::PhantomData);
+    }
+}
+
+#[test]
+fn phantom_lifetime_token() {
+    let provider: Box<dyn Provider> = Box::new(Token);
+    assert!(provider.request::<tag::PhantomLifetime>().is_some());
+    let greeting: Box<dyn Provider> = Box::new(Greeting);
+    assert!(greeting.request::<tag::PhantomLifetime>().is_none());
+}
+
+struct Words {
+    text: String,
+}
+
+impl Provider for Words {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide_with::<tag::Iter<tag::Ref<str>>, _>(|| Box::new(self.text.split_whitespace()));
+    }
+}
+
+#[test]
+fn provide_iter() {
+    let provider: Box<dyn Provider> = Box::new(Words {
+        text: "one two three".to_owned(),
+    });
+    let words = provider.request::<tag::Iter<tag::Ref<str>>>().unwrap();
+    assert_eq!(words.collect::<Vec<_>>(), ["one", "two", "three"]);
+}
+
+struct Remote;
+
+impl Remote {
+    fn answer<'a>(&'a self, id: TypeId) -> Option<Box<dyn Tagged<'a> + 'a>> {
+        if id == TypeId::of::<tag::Optional<tag::Value<u32>>>() {
+            Some(<dyn Tagged>::tag_box::<tag::Optional<tag::Value<u32>>>(
+                Box::new(Some(42)),
+            ))
+        } else if id == TypeId::of::<tag::Optional<tag::Ref<str>>>() {
+            Some(<dyn Tagged>::tag_box::<tag::Optional<tag::Ref<str>>>(
+                Box::new(Some("remote")),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+struct Proxy {
+    remote: Remote,
+    ids: Vec<TypeId>,
+}
+
+impl Provider for Proxy {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        for &id in &self.ids {
+            if request.matches_id(id) {
+                if let Some(mut value) = self.remote.answer(id) {
+                    request.provide_erased(&mut *value);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn forward_by_type_id() {
+    let proxy: Box<dyn Provider> = Box::new(Proxy {
+        remote: Remote,
+        ids: vec![
+            TypeId::of::<tag::Optional<tag::Value<u32>>>(),
+            TypeId::of::<tag::Optional<tag::Ref<str>>>(),
+        ],
+    });
+
+    assert_eq!(proxy.request::<tag::Value<u32>>(), Some(42));
+    assert_eq!(proxy.request::<tag::Ref<str>>(), Some("remote"));
+    assert_eq!(proxy.request::<tag::Value<u64>>(), None);
+
+    let value = provider::request::<tag::Value<u32>, _>(|request| {
+        request.provide::<tag::Value<u32>>(1);
+        proxy.provide(request);
+    });
+    assert_eq!(value, Some(1));
+
+    let mut batch = provider::Batch::new().with::<tag::Ref<str>>();
+    batch.fulfill(|request| proxy.provide(request));
+    assert_eq!(batch.take::<tag::Ref<str>>(), Some("remote"));
+}
+
+struct Num<const N: usize>;
+
+impl<'a, const N: usize> dyno::Tag<'a> for Num<N> {
+    type Type = usize;
+}
+
+struct Many;
+
+impl Provider for Many {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide::<Num<0>>(0)
+            .provide::<Num<1>>(1)
+            .provide::<Num<2>>(2)
+            .provide::<Num<3>>(3);
+    }
+}
+
+#[test]
+fn match_many_tags() {
+    let provider: &dyn Provider = &Many;
+    assert_eq!(provider.request::<Num<0>>(), Some(0));
+    assert_eq!(provider.request::<Num<1>>(), Some(1));
+    assert_eq!(provider.request::<Num<2>>(), Some(2));
+    assert_eq!(provider.request::<Num<3>>(), Some(3));
+    assert_eq!(provider.request::<Num<4>>(), None);
+}
+
+struct Named(&'static str);
+
+impl Provider for Named {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide::<tag::Ref<str>>(self.0);
+    }
+}
+
+#[test]
+fn chain_first_wins() {
+    let chained: Box<dyn Provider> = Box::new(provider::chain!(
+        Greeting,
+        Named("first"),
+        Named("second"),
+        Capable
+    ));
+    assert_eq!(chained.request::<tag::Ref<str>>(), Some("first"));
+    assert_eq!(
+        chained.request::<tag::Value<String>>().as_deref(),
+        Some("hello")
+    );
+    assert_eq!(chained.request::<tag::Unit>(), Some(()));
+    assert_eq!(chained.request::<tag::Value<u32>>(), None);
+}
+
+struct Declining;
+
+impl Provider for Declining {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_computed::<tag::Ref<str>, _>(|| None);
+    }
+}
+
+#[test]
+fn provide_computed_declines() {
+    let declining: Box<dyn Provider> = Box::new(Declining);
+    assert_eq!(declining.request::<tag::Ref<str>>(), None);
+
+    let chained: Box<dyn Provider> = Box::new(Declining.or(Named("later")));
+    assert_eq!(chained.request::<tag::Ref<str>>(), Some("later"));
+}
+
+#[test]
+fn from_fn_provider() {
+    let greeting = String::from("hi");
+    let provider = provider::from_fn(move |request| {
+        request
+            .provide_with::<tag::Value<String>, _>(|| greeting.clone())
+            .provide::<tag::Ref<str>>("static");
+    });
+    let provider: &dyn Provider = &provider;
+
+    assert_eq!(
+        provider.request::<tag::Value<String>>().as_deref(),
+        Some("hi")
+    );
+    assert_eq!(provider.request::<tag::Ref<str>>(), Some("static"));
+    assert_eq!(provider.request::<tag::Value<i32>>(), None);
+}
+
+struct Fields {
+    name: String,
+    scores: Vec<u32>,
+}
+
+impl Provider for Fields {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        // Without `provide_borrowed`, each field needs its tag spelled out:
+        // `request.provide::<tag::Ref<String>>(&self.name)`.
+        request
+            .provide_borrowed(&self.name)
+            .provide_borrowed(self.name.as_str())
+            .provide_borrowed(&self.scores[..]);
+    }
+}
+
+#[test]
+fn provide_borrowed_fields() {
+    let fields: Box<dyn Provider> = Box::new(Fields {
+        name: "fields".to_owned(),
+        scores: vec![1, 2, 3],
+    });
+    assert_eq!(
+        fields.request::<tag::Ref<String>>().map(String::as_str),
+        Some("fields")
+    );
+    assert_eq!(fields.request::<tag::Ref<str>>(), Some("fields"));
+    assert_eq!(fields.request::<tag::Ref<[u32]>>(), Some(&[1, 2, 3][..]));
+}
+
+#[test]
+fn request_or_defaults() {
+    let named: Box<dyn Provider> = Box::new(Named("named"));
+    assert_eq!(named.request_ref_or::<str>("default"), "named");
+    assert_eq!(named.request_value_or(7u8), 7);
+
+    let greeting: Box<dyn Provider> = Box::new(Greeting);
+    assert_eq!(greeting.request_ref_or::<str>("default"), "default");
+    assert_eq!(greeting.request_value_or(String::new()), "hello");
+}
+
+#[test]
+fn expect_matching_tag() {
+    let _ = provider::request::<tag::Ref<str>, _>(|request| {
+        request.expect::<tag::Ref<str>>();
+    });
+}
+
+#[test]
+#[should_panic(expected = "expected a request for `dyno::tag::Value<i32>`")]
+fn expect_mismatched_tag() {
+    let _ = provider::request::<tag::Ref<str>, _>(|request| {
+        request.expect::<tag::Value<i32>>();
+    });
+}
+
+#[test]
+fn tuple_providers() {
+    let empty: Box<dyn Provider> = Box::new(());
+    assert_eq!(empty.request::<tag::Ref<str>>(), None);
+
+    let pair: Box<dyn Provider> = Box::new((Named("first"), Greeting));
+    assert_eq!(pair.request::<tag::Ref<str>>(), Some("first"));
+    assert_eq!(
+        pair.request::<tag::Value<String>>().as_deref(),
+        Some("hello")
+    );
+
+    let triple: Box<dyn Provider> = Box::new(((), Named("second"), Named("third")));
+    assert_eq!(triple.request::<tag::Ref<str>>(), Some("second"));
+
+    // Later elements are not consulted once the request is fulfilled.
+    let counted = (
+        Named("first"),
+        Counted {
+            name: "later".to_owned(),
+            calls: Cell::new(0),
+        },
+    );
+    let value = provider::request::<tag::Ref<str>, _>(|request| counted.provide(request));
+    assert_eq!(value, Some("first"));
+    assert_eq!(counted.1.calls.get(), 0);
+}
+
+#[test]
+fn request_first_stops_at_hit() {
+    let third = Counted {
+        name: "third".to_owned(),
+        calls: Cell::new(0),
+    };
+    let providers: [&dyn Provider; 3] = [&Named("first"), &Greeting, &third];
+
+    let value = provider::request_first::<tag::Value<String>>(&providers);
+    assert_eq!(value.as_deref(), Some("hello"));
+    assert_eq!(third.calls.get(), 0);
+
+    let value = provider::request_first::<tag::Value<u32>>(&providers);
+    assert_eq!(value, None);
+    assert_eq!(third.calls.get(), 1);
+}
+
+struct Cloning {
+    name: String,
+}
+
+impl Provider for Cloning {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_owned(&self.name);
+    }
+}
+
+#[test]
+fn provide_owned_clones() {
+    let owned = {
+        let cloning: Box<dyn Provider> = Box::new(Cloning {
+            name: "cloned".to_owned(),
+        });
+        cloning.request::<tag::Owned<String>>()
+    };
+    assert_eq!(owned.as_deref(), Some("cloned"));
+}
+
+struct Gated {
+    public: bool,
+    secret: String,
+}
+
+impl Provider for Gated {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide_ref_if::<str>(self.public, &self.secret)
+            .provide_ref_if::<str>(true, "fallback");
+    }
+}
+
+#[test]
+fn provide_ref_if_gates() {
+    let public: Box<dyn Provider> = Box::new(Gated {
+        public: true,
+        secret: "secret".to_owned(),
+    });
+    assert_eq!(public.request::<tag::Ref<str>>(), Some("secret"));
+
+    let private: Box<dyn Provider> = Box::new(Gated {
+        public: false,
+        secret: "secret".to_owned(),
+    });
+    assert_eq!(private.request::<tag::Ref<str>>(), Some("fallback"));
+}
+
+struct Textual;
+
+impl Provider for Textual {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        let ids = [
+            provider::req_tag_id::<tag::Ref<str>>(),
+            provider::req_tag_id::<tag::Value<String>>(),
+        ];
+        if request.matches_any(&ids) {
+            request
+                .provide::<tag::Ref<str>>("text")
+                .provide_with::<tag::Value<String>, _>(|| "text".to_owned());
+        }
+    }
+}
+
+#[test]
+fn matches_any_gates_block() {
+    assert_eq!(
+        provider::req_tag_id::<tag::Ref<str>>(),
+        TypeId::of::<tag::Optional<tag::Ref<str>>>()
+    );
+
+    let textual: Box<dyn Provider> = Box::new(Textual);
+    assert_eq!(textual.request::<tag::Ref<str>>(), Some("text"));
+    assert_eq!(
+        textual.request::<tag::Value<String>>().as_deref(),
+        Some("text")
+    );
+    let _ = provider::request::<tag::Value<u32>, _>(|request| {
+        assert!(!request.matches_any(&[provider::req_tag_id::<tag::Ref<str>>()]));
+        assert!(request.matches_any(&[
+            provider::req_tag_id::<tag::Ref<str>>(),
+            provider::req_tag_id::<tag::Value<u32>>(),
+        ]));
+    });
+}
+
+struct Buffer {
+    data: Vec<u8>,
+}
+
+impl provider::ProviderMut for Buffer {
+    fn provide_mut<'a>(&'a mut self, request: &mut Request<'a>) {
+        request.provide::<tag::RefMut<[u8]>>(&mut self.data);
+    }
+}
+
+#[test]
+fn request_mut_writes_through() {
+    let mut buffer = Buffer { data: vec![0; 3] };
+    if let Some(slice) = provider::request_mut::<tag::RefMut<[u8]>, _>(&mut buffer) {
+        slice[1] = 7;
+    }
+    assert_eq!(buffer.data, [0, 7, 0]);
+
+    let erased: &mut dyn provider::ProviderMut = &mut buffer;
+    assert!(provider::request_mut::<tag::Ref<[u8]>, _>(erased).is_none());
+}
+
+#[test]
+fn provide_from_iter_consumes_one() {
+    let consumed = Cell::new(0);
+    let candidates = || (1..=3u32).inspect(|_| consumed.set(consumed.get() + 1));
+
+    let value = provider::request::<tag::Value<u32>, _>(|request| {
+        request
+            .provide_from_iter::<tag::Value<u32>, _>(candidates())
+            .provide_from_iter::<tag::Value<u32>, _>(candidates());
+    });
+    assert_eq!(value, Some(1));
+    assert_eq!(consumed.get(), 1);
+
+    let value = provider::request::<tag::Value<u8>, _>(|request| {
+        request.provide_from_iter::<tag::Value<u32>, _>(candidates());
+    });
+    assert_eq!(value, None);
+    assert_eq!(consumed.get(), 1);
+}
+
+struct Empty;
+
+impl Provider for Empty {}
+
+#[test]
+fn empty_provider() {
+    let empty: Box<dyn Provider> = Box::new(Empty);
+    assert_eq!(empty.request::<tag::Ref<str>>(), None);
+    assert_eq!(empty.request::<tag::Value<u32>>(), None);
+    assert_eq!(empty.request::<tag::Unit>(), None);
+}
+
+struct Rendered {
+    count: u32,
+}
+
+impl Provider for Rendered {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_borrowed_as::<tag::Value<String>, str>(format!("{} items", self.count));
+    }
+}
+
+#[test]
+fn borrowed_as_lends_owned_value() {
+    let rendered = Rendered { count: 3 };
+    let len = provider::request_borrowed_as::<tag::Value<String>, str, _, _, _>(
+        |request| rendered.provide(request),
+        |text| {
+            assert_eq!(text, Some("3 items"));
+            text.map(str::len)
+        },
+    );
+    assert_eq!(len, Some(7));
+
+    let missing = provider::request_borrowed_as::<tag::Value<Vec<u8>>, [u8], _, _, _>(
+        |request| rendered.provide(request),
+        |bytes| bytes.is_none(),
+    );
+    assert!(missing);
+}
+
+#[test]
+fn request_expect_provided() {
+    let named: Box<dyn Provider> = Box::new(Named("expected"));
+    assert_eq!(named.request_expect::<tag::Ref<str>>(), "expected");
+}
+
+#[test]
+#[should_panic(expected = "provider did not provide a value for `dyno::tag::Value<u32>`")]
+fn request_expect_missing() {
+    let named: Box<dyn Provider> = Box::new(Named("expected"));
+    let _ = named.request_expect::<tag::Value<u32>>();
+}
+
+struct Settings;
+
+impl Provider for Settings {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide_keyed::<tag::Value<u32>, _>(&"timeout", 30)
+            .provide_keyed::<tag::Value<u32>, _>(&"retries", 3)
+            .provide_keyed::<tag::Value<u32>, _>(&"retries", 5);
+    }
+}
+
+#[test]
+fn keyed_requests() {
+    let timeout = provider::request_keyed::<tag::Value<u32>, _, _>("timeout", |request| {
+        Settings.provide(request)
+    });
+    assert_eq!(timeout, Some(30));
+
+    let retries = provider::request_keyed::<tag::Value<u32>, _, _>("retries", |request| {
+        Settings.provide(request)
+    });
+    assert_eq!(retries, Some(3));
+
+    let missing = provider::request_keyed::<tag::Value<u32>, _, _>("missing", |request| {
+        Settings.provide(request)
+    });
+    assert_eq!(missing, None);
+
+    let plain: Box<dyn Provider> = Box::new(Settings);
+    assert_eq!(plain.request::<tag::Value<u32>>(), None);
+}
+
+#[test]
+fn keyed_request_rejects_erased_provision() {
+    let mut other = provider::KeyedRequest::<&str, tag::Value<u32>>::new("timeout");
+    other.fulfill(|request| Settings.provide(request));
+
+    // Providing another keyed request as an erased value must not bypass the
+    // key check in `provide_keyed`.
+    let value = provider::request_keyed::<tag::Value<u32>, _, _>("missing", |request| {
+        request.provide_erased(&mut other);
+    });
+    assert_eq!(value, None);
+    assert_eq!(other.key(), &"timeout");
+    assert_eq!(other.take(), Some(30));
+}
+
+struct Either {
+    name: String,
+}
+
+impl Provider for Either {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref_or_value(&self.name);
+    }
+}
+
+#[test]
+fn provide_ref_or_value_forms() {
+    let either: Box<dyn Provider> = Box::new(Either {
+        name: "either".to_owned(),
+    });
+    assert_eq!(
+        either.request::<tag::Ref<String>>().map(String::as_str),
+        Some("either")
+    );
+    assert_eq!(
+        either.request::<tag::Value<String>>().as_deref(),
+        Some("either")
+    );
+    assert_eq!(either.request::<tag::Ref<str>>(), None);
+}
+
+struct Advertised;
+
+impl Provider for Advertised {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide::<tag::Ref<str>>("advertised")
+            .provide::<tag::Value<u32>>(2);
+    }
+
+    fn provided_tags(&self) -> Vec<TypeId> {
+        vec![
+            provider::req_tag_id::<tag::Ref<str>>(),
+            provider::req_tag_id::<tag::Value<u32>>(),
+        ]
+    }
+}
+
+#[test]
+fn provided_tags_introspection() {
+    let advertised: Box<dyn Provider> = Box::new(Advertised);
+    assert_eq!(
+        advertised.provided_tags(),
+        [
+            provider::req_tag_id::<tag::Ref<str>>(),
+            provider::req_tag_id::<tag::Value<u32>>(),
+        ]
+    );
+
+    let empty: Box<dyn Provider> = Box::new(Empty);
+    assert!(empty.provided_tags().is_empty());
+}
+
+#[test]
+fn provided_tags_forwarded_by_wrappers() {
+    let tags = [
+        provider::req_tag_id::<tag::Ref<str>>(),
+        provider::req_tag_id::<tag::Value<u32>>(),
+    ];
+    assert_eq!(Some(Advertised).provided_tags(), tags);
+    assert!(None::<Advertised>.provided_tags().is_empty());
+    assert_eq!(Advertised.or(()).provided_tags(), tags);
+    assert_eq!(Empty.or(Advertised).provided_tags(), tags);
+    assert_eq!((Advertised, Empty, Advertised).provided_tags(), tags);
+    assert_eq!(provider::Cached::new(Advertised).provided_tags(), tags);
+    assert_eq!([Advertised, Advertised].provided_tags(), tags);
+    assert_eq!(Arc::new(Advertised).provided_tags(), tags);
+    assert_eq!(Rc::new(Advertised).provided_tags(), tags);
+    assert_eq!(
+        provider::Filtered::new(Arc::new(Advertised), &tags[1..]).provided_tags(),
+        &tags[1..]
+    );
+    assert!(<[Empty]>::provided_tags(&[]).is_empty());
+
+    let mapped =
+        provider::MapTag::<_, tag::Value<u32>, tag::Value<u64>, _>::new(Advertised, u64::from);
+    assert_eq!(
+        mapped.provided_tags(),
+        [provider::req_tag_id::<tag::Value<u64>>()]
+    );
+    let unmapped =
+        provider::MapTag::<_, tag::Value<u8>, tag::Value<u64>, _>::new(Advertised, u64::from);
+    assert!(unmapped.provided_tags().is_empty());
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Short,
+    Long,
+}
+
+struct Formatted(Format);
+
+impl<'a> dyno::Tag<'a> for Formatted {
+    type Type = String;
+}
+
+struct Clock;
+
+impl Provider for Clock {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        let format = request.tag_data::<Formatted>().map(|tag| tag.0);
+        request.provide_with::<Formatted, _>(|| match format {
+            Some(Format::Long) => "twelve o'clock".to_owned(),
+            Some(Format::Short) | None => "12:00".to_owned(),
+        });
+    }
+}
+
+#[test]
+fn tag_carrying_data() {
+    let long = provider::request_with_tag(Formatted(Format::Long), |request| {
+        assert!(request.is::<Formatted>());
+        Clock.provide(request)
+    });
+    assert_eq!(long.as_deref(), Some("twelve o'clock"));
+
+    let short =
+        provider::request_with_tag(Formatted(Format::Short), |request| Clock.provide(request));
+    assert_eq!(short.as_deref(), Some("12:00"));
+
+    let plain = provider::request::<Formatted, _>(|request| {
+        assert!(request.tag_data::<Formatted>().is_none());
+        Clock.provide(request)
+    });
+    assert_eq!(plain.as_deref(), Some("12:00"));
+}
+
+type Parsed = tag::Result<tag::Value<u32>, tag::Ref<str>>;
+
+struct Parser(&'static str);
+
+impl Provider for Parser {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        match self.0.parse() {
+            Ok(value) => request.provide_ok::<tag::Value<u32>, tag::Ref<str>>(value),
+            Err(_) => request.provide_err::<tag::Value<u32>, tag::Ref<str>>("invalid number"),
+        };
+    }
+}
+
+#[test]
+fn provide_ok_and_err() {
+    let ok: Box<dyn Provider> = Box::new(Parser("12"));
+    assert_eq!(ok.request::<Parsed>(), Some(Ok(12)));
+
+    let err: Box<dyn Provider> = Box::new(Parser("twelve"));
+    assert_eq!(err.request::<Parsed>(), Some(Err("invalid number")));
+}
+
+struct Cancelling<'t>(&'t provider::CancelToken);
+
+impl Provider for Cancelling<'_> {
+    fn provide<'a>(&'a self, _request: &mut Request<'a>) {
+        self.0.cancel();
+    }
+}
+
+#[test]
+fn cancellation_stops_chain() {
+    let token = provider::CancelToken::new();
+    let chain = Cancelling(&token).or(Counted {
+        name: "later".to_owned(),
+        calls: Cell::new(0),
+    });
+
+    let value = provider::request_cancellable::<tag::Ref<str>, _>(&token, |request| {
+        assert!(!request.is_cancelled());
+        chain.provide(request);
+        assert!(request.is_cancelled());
+    });
+    assert_eq!(value, None);
+    assert_eq!(chain.1.calls.get(), 0);
+
+    let value = provider::request::<tag::Ref<str>, _>(|request| chain.provide(request));
+    assert_eq!(value, Some("later"));
+    assert_eq!(chain.1.calls.get(), 1);
+}
+
+#[test]
+fn cancellation_stops_tuple_and_request_first() {
+    let token = provider::CancelToken::new();
+    let tuple = (
+        Cancelling(&token),
+        Counted {
+            name: "later".to_owned(),
+            calls: Cell::new(0),
+        },
+    );
+    let value = provider::request_cancellable::<tag::Ref<str>, _>(&token, |request| {
+        tuple.provide(request);
+    });
+    assert_eq!(value, None);
+    assert_eq!(tuple.1.calls.get(), 0);
+
+    let providers: [&dyn Provider; 2] = [&tuple.0, &tuple.1];
+    let value = provider::request_first_cancellable::<tag::Ref<str>>(&token, &providers);
+    assert_eq!(value, None);
+    assert_eq!(tuple.1.calls.get(), 0);
+
+    let fresh = provider::CancelToken::new();
+    let providers: [&dyn Provider; 2] = [&Named("first"), &tuple.1];
+    let value = provider::request_first_cancellable::<tag::Ref<str>>(&fresh, &providers);
+    assert_eq!(value, Some("first"));
+    assert_eq!(tuple.1.calls.get(), 0);
+}
+
+#[test]
+fn array_and_slice_providers() {
+    let array: Box<dyn Provider> = Box::new([Named("first"), Named("second"), Named("third")]);
+    assert_eq!(array.request::<tag::Ref<str>>(), Some("first"));
+    assert_eq!(array.request::<tag::Value<u32>>(), None);
+
+    let providers = [Named("first"), Named("second"), Named("third")];
+    let slice: &[Named] = &providers[1..];
+    let value = provider::request::<tag::Ref<str>, _>(|request| slice.provide(request));
+    assert_eq!(value, Some("second"));
+}
+
+#[test]
+fn slice_providers_stop_early() {
+    let counted = |name: &str| Counted {
+        name: name.to_owned(),
+        calls: Cell::new(0),
+    };
+
+    // Later elements are not consulted once the request is fulfilled.
+    let providers = [counted("first"), counted("second")];
+    let value = provider::request::<tag::Ref<str>, _>(|request| providers.provide(request));
+    assert_eq!(value, Some("first"));
+    assert_eq!(providers[1].calls.get(), 0);
+
+    // Nor once it is cancelled.
+    let token = provider::CancelToken::new();
+    let providers = [counted("first"), counted("second")];
+    let value = provider::request_cancellable::<tag::Ref<[u8]>, _>(&token, |request| {
+        token.cancel();
+        providers[..].provide(request);
+    });
+    assert_eq!(value, None);
+    assert_eq!(providers[0].calls.get(), 1);
+    assert_eq!(providers[1].calls.get(), 0);
+}
+
+struct Defaults;
+
+impl Provider for Defaults {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide_default::<tag::Value<u32>>()
+            .provide_default::<tag::Value<String>>();
+    }
+}
+
+#[test]
+fn provide_default_last_resort() {
+    let chained: Box<dyn Provider> = Box::new(Greeting.or(Defaults));
+    assert_eq!(
+        chained.request::<tag::Value<String>>().as_deref(),
+        Some("hello")
+    );
+    assert_eq!(chained.request::<tag::Value<u32>>(), Some(0));
+    assert_eq!(chained.request::<tag::Ref<str>>(), None);
+}
+
+#[test]
+fn shared_providers() {
+    let named = Arc::new(Named("shared"));
+    let arc: Box<dyn Provider> = Box::new(named.clone());
+    assert_eq!(arc.request::<tag::Ref<str>>(), Some("shared"));
+    assert_eq!(Arc::strong_count(&named), 2);
+
+    let erased: Rc<dyn Provider> = Rc::new(Greeting);
+    let rc: Box<dyn Provider> = Box::new(erased);
+    assert_eq!(rc.request::<tag::Value<String>>().as_deref(), Some("hello"));
+}
+
+#[test]
+fn wants_ref_and_value() {
+    let _ = provider::request::<tag::Ref<str>, _>(|request| {
+        assert!(request.wants_ref::<str>());
+        assert!(!request.wants_ref::<String>());
+        assert!(!request.wants_value::<String>());
+    });
+    let _ = provider::request::<tag::Value<String>, _>(|request| {
+        assert!(request.wants_value::<String>());
+        assert!(!request.wants_value::<u32>());
+        assert!(!request.wants_ref::<String>());
+    });
+}
+
+#[test]
+fn replace_returns_previous() {
+    let value = provider::request::<tag::Value<u32>, _>(|request| {
+        assert_eq!(request.replace::<tag::Value<u32>>(1), None);
+        assert_eq!(request.replace::<tag::Value<u32>>(2), Some(1));
+        assert_eq!(request.replace::<tag::Value<u8>>(3), None);
+    });
+    assert_eq!(value, Some(2));
+}
+
+#[test]
+fn filtered_provider_refuses() {
+    let filtered: Box<dyn Provider> = Box::new(provider::Filtered::new(
+        Advertised,
+        &[provider::req_tag_id::<tag::Value<u32>>()],
+    ));
+    assert_eq!(filtered.request::<tag::Value<u32>>(), Some(2));
+    assert_eq!(filtered.request::<tag::Ref<str>>(), None);
+    assert_eq!(
+        filtered.provided_tags(),
+        [provider::req_tag_id::<tag::Value<u32>>()]
+    );
+
+    // Only the allowlisted slots of a batch are forwarded.
+    let mut batch = provider::Batch::new()
+        .with::<tag::Value<u32>>()
+        .with::<tag::Ref<str>>();
+    batch.fulfill(|request| filtered.provide(request));
+    assert_eq!(batch.take::<tag::Value<u32>>(), Some(2));
+    assert_eq!(batch.take::<tag::Ref<str>>(), None);
+
+    // Keyed requests are matched by the request id of their tag.
+    let settings = provider::Filtered::new(Settings, &[provider::req_tag_id::<tag::Value<u32>>()]);
+    let timeout = provider::request_keyed::<tag::Value<u32>, _, _>("timeout", |request| {
+        settings.provide(request)
+    });
+    assert_eq!(timeout, Some(30));
+    let refused = provider::Filtered::new(Settings, &[provider::req_tag_id::<tag::Value<u8>>()]);
+    let timeout = provider::request_keyed::<tag::Value<u32>, _, _>("timeout", |request| {
+        refused.provide(request)
+    });
+    assert_eq!(timeout, None);
+}
+
+struct Squarer;
+
+impl Provider for Squarer {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide_computed_from::<tag::Value<u32>, tag::Value<u64>, _>(|n| {
+                u64::from(*n) * u64::from(*n)
+            })
+            .provide_computed_from::<tag::Ref<str>, tag::Value<usize>, _>(|s| s.len());
+    }
+}
+
+#[test]
+fn arg_requests() {
+    let squared = provider::request_with_arg::<tag::Value<u32>, tag::Value<u64>, _>(7, |request| {
+        Squarer.provide(request)
+    });
+    assert_eq!(squared, Some(49));
+
+    let len =
+        provider::request_with_arg::<tag::Ref<str>, tag::Value<usize>, _>("four", |request| {
+            Squarer.provide(request)
+        });
+    assert_eq!(len, Some(4));
+
+    let plain: Box<dyn Provider> = Box::new(Squarer);
+    assert_eq!(plain.request::<tag::Value<u64>>(), None);
+}
+
+#[test]
+fn arg_request_rejects_erased_provision() {
+    let mut other = provider::ArgRequest::<tag::Value<u32>, tag::Value<u64>>::new(3);
+    other.fulfill(|request| Squarer.provide(request));
+
+    // Providing another argument request as an erased value must not replace
+    // the request's argument.
+    let value = provider::request_with_arg::<tag::Value<u32>, tag::Value<u64>, _>(7, |request| {
+        request.provide_erased(&mut other);
+        Squarer.provide(request);
+    });
+    assert_eq!(value, Some(49));
+    assert_eq!(other.arg(), &3);
+    assert_eq!(other.take(), Some(9));
+}
+
+#[test]
+fn filtered_arg_requests() {
+    let allowed = provider::Filtered::new(Squarer, &[provider::req_tag_id::<tag::Value<u64>>()]);
+    let squared = provider::request_with_arg::<tag::Value<u32>, tag::Value<u64>, _>(7, |request| {
+        allowed.provide(request)
+    });
+    assert_eq!(squared, Some(49));
+
+    let len =
+        provider::request_with_arg::<tag::Ref<str>, tag::Value<usize>, _>("four", |request| {
+            allowed.provide(request)
+        });
+    assert_eq!(len, None);
+}
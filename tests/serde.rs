@@ -0,0 +1,9 @@
+#![cfg(feature = "serde")]
+
+use dyno::{tag, TagValue};
+
+#[test]
+fn serialize_tag_value() {
+    let value = TagValue::<tag::Value<u32>>(5);
+    assert_eq!(serde_json::to_string(&value).unwrap(), "5");
+}
@@ -0,0 +1,449 @@
+use dyno::provider::request;
+use dyno::tag::{self, BytesExt};
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[test]
+fn request_dyn_ref() {
+    let value = vec![1, 2, 3];
+    let debug = request::<tag::DynRef<dyn Debug>, _>(|request| {
+        request.provide::<tag::DynRef<dyn Debug>>(&value);
+    });
+    assert_eq!(format!("{:?}", debug.unwrap()), "[1, 2, 3]");
+}
+
+const REF: tag::Ref<str> = tag::Ref::new();
+const REF_MUT: tag::RefMut<[u8]> = tag::RefMut::new();
+const VALUE: tag::Value<u32> = tag::Value::new();
+const DYN_REF: tag::DynRef<dyn Debug> = tag::DynRef::new();
+const DYN_REF_MUT: tag::DynRefMut<dyn Debug> = tag::DynRefMut::new();
+const OPTIONAL: tag::Optional<tag::Value<u32>> = tag::Optional::new();
+
+#[test]
+fn const_tags() {
+    let _ = (REF, REF_MUT, VALUE, DYN_REF, DYN_REF_MUT, OPTIONAL);
+}
+
+#[test]
+fn request_tuple3() {
+    type Triple = tag::Tuple3<tag::Ref<str>, tag::Value<u32>, tag::Value<String>>;
+
+    let name = String::from("jane");
+    let value = request::<Triple, _>(|request| {
+        request.provide::<Triple>((&name, 7, "owned".to_owned()));
+    });
+    assert_eq!(value, Some(("jane", 7, "owned".to_owned())));
+}
+
+#[test]
+fn request_nested_optional() {
+    type Nested = tag::Optional<tag::Optional<tag::Value<u32>>>;
+
+    let unfulfilled = request::<Nested, _>(|_| {});
+    assert_eq!(unfulfilled, None);
+
+    let outer_none = request::<Nested, _>(|request| {
+        request.provide::<Nested>(None);
+    });
+    assert_eq!(outer_none, Some(None));
+
+    let inner_none = request::<Nested, _>(|request| {
+        request.provide::<Nested>(Some(None));
+    });
+    assert_eq!(inner_none, Some(Some(None)));
+
+    let value = request::<Nested, _>(|request| {
+        request.provide::<Nested>(Some(Some(5)));
+    });
+    assert_eq!(value, Some(Some(Some(5))));
+}
+
+#[test]
+fn request_bytes() {
+    let buffer = [0x78, 0x56, 0x34, 0x12, 0xff];
+    let bytes = request::<tag::Bytes, _>(|request| {
+        request.provide::<tag::Bytes>(&buffer);
+    })
+    .unwrap();
+    assert_eq!(bytes.read_u32_le(0), Some(0x1234_5678));
+    assert_eq!(bytes.read_u32_be(1), Some(0x5634_12ff));
+    assert_eq!(bytes.read_u32_le(2), None);
+    assert_eq!(bytes.read_u64_le(0), None);
+    assert_eq!(bytes.read_u32_le(usize::MAX), None);
+}
+
+#[test]
+fn request_rc_and_arc() {
+    let shared = Rc::new(5u32);
+    let rc = request::<tag::RcType<u32>, _>(|request| {
+        request.provide::<tag::RcType<u32>>(shared.clone());
+    });
+    assert!(Rc::ptr_eq(&rc.unwrap(), &shared));
+
+    let arc = request::<tag::ArcType<str>, _>(|request| {
+        request.provide::<tag::ArcType<str>>(Arc::from("shared"));
+    });
+    assert_eq!(arc.as_deref(), Some("shared"));
+}
+
+#[test]
+fn request_static() {
+    let value = request::<tag::Static<&'static str>, _>(|request| {
+        request.provide::<tag::Static<&'static str>>("static");
+    });
+    assert_eq!(value, Some("static"));
+}
+
+#[test]
+fn request_ranges() {
+    let range = request::<tag::Range<usize>, _>(|request| {
+        request
+            .provide::<tag::Range<usize>>(0..10)
+            .provide::<tag::RangeInclusive<usize>>(0..=9);
+    });
+    assert_eq!(range, Some(0..10));
+
+    let inclusive = request::<tag::RangeInclusive<usize>, _>(|request| {
+        request.provide::<tag::RangeInclusive<usize>>(0..=9);
+    });
+    assert_eq!(inclusive, Some(0..=9));
+}
+
+#[test]
+fn request_str_and_cstr() {
+    let owned = String::from("text");
+    let text = request::<tag::Str, _>(|request| {
+        request.provide::<tag::Str>(&owned);
+    });
+    assert_eq!(text, Some("text"));
+
+    let cstring = std::ffi::CString::new("c text").unwrap();
+    let ctext = request::<tag::CStr, _>(|request| {
+        request.provide::<tag::CStr>(&cstring);
+    });
+    assert_eq!(ctext.map(|s| s.to_bytes()), Some(&b"c text"[..]));
+}
+
+#[test]
+fn request_map_ref() {
+    let mut btree = std::collections::BTreeMap::new();
+    btree.insert("key", 1);
+    let map = request::<tag::MapRef<&'static str, i32>, _>(|request| {
+        request.provide::<tag::MapRef<&'static str, i32>>(&btree);
+    });
+    assert_eq!(map.and_then(|map| map.get("key")), Some(&1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn request_hash_map_ref() {
+    let mut hash = std::collections::HashMap::new();
+    hash.insert(2, "two");
+    let map = request::<tag::HashMapRef<i32, &'static str>, _>(|request| {
+        request.provide::<tag::HashMapRef<i32, &'static str>>(&hash);
+    });
+    assert_eq!(map.and_then(|map| map.get(&2)), Some(&"two"));
+}
+
+#[test]
+fn request_display_and_debug_refs() {
+    let value = 42;
+    let display = request::<tag::DisplayRef, _>(|request| {
+        request
+            .provide::<tag::DisplayRef>(&value)
+            .provide::<tag::DebugRef>(&value);
+    });
+    assert_eq!(display.map(|d| d.to_string()).as_deref(), Some("42"));
+
+    let name = "name";
+    let debug = request::<tag::DebugRef, _>(|request| {
+        request.provide::<tag::DebugRef>(&name);
+    });
+    assert_eq!(
+        debug.map(|d| format!("{:?}", d)).as_deref(),
+        Some("\"name\"")
+    );
+}
+
+#[test]
+fn request_pin_box_future() {
+    use std::task::{Context, Poll, Waker};
+
+    let base = 40;
+    let future = request::<tag::PinBoxFuture<tag::Value<i32>>, _>(|request| {
+        request.provide::<tag::PinBoxFuture<tag::Value<i32>>>(Box::pin(async { base + 2 }));
+    });
+
+    let mut future = future.unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    let result = loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            break value;
+        }
+    };
+    assert_eq!(result, 42);
+}
+
+#[test]
+fn request_raw_pointers() {
+    let mut value = 5u32;
+    let ptr: *mut u32 = &mut value;
+
+    let mut_ptr = request::<tag::RawMutPtr<u32>, _>(|request| {
+        request.provide::<tag::RawMutPtr<u32>>(ptr);
+    });
+    assert_eq!(mut_ptr, Some(ptr));
+
+    let const_ptr = request::<tag::RawConstPtr<u32>, _>(|request| {
+        request.provide::<tag::RawConstPtr<u32>>(ptr as *const u32);
+    });
+    // SAFETY: `value` is still live and not mutably borrowed.
+    assert_eq!(const_ptr.map(|ptr| unsafe { *ptr }), Some(5));
+
+    let non_null = request::<tag::NonNullPtr<u32>, _>(|request| {
+        request.provide::<tag::NonNullPtr<u32>>(std::ptr::NonNull::new(ptr).unwrap());
+    });
+    assert_eq!(non_null.map(|ptr| ptr.as_ptr()), Some(ptr));
+}
+
+#[test]
+fn request_lazy() {
+    let evaluated = std::cell::Cell::new(false);
+    let lazy = request::<tag::Lazy<tag::Value<i32>>, _>(|request| {
+        request.provide::<tag::Lazy<tag::Value<i32>>>(Box::new(|| {
+            evaluated.set(true);
+            42
+        }));
+    });
+
+    let lazy = lazy.unwrap();
+    assert!(!evaluated.get());
+    assert_eq!(lazy(), 42);
+    assert!(evaluated.get());
+
+    let boxed = request::<tag::BoxedFnOnce<tag::Value<i32>>, _>(|request| {
+        request.provide::<tag::Lazy<tag::Value<i32>>>(Box::new(|| 0));
+    });
+    assert!(boxed.is_none());
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn request_small_vec_value() {
+    let values = request::<tag::SmallVecValue<u8, 4>, _>(|request| {
+        request.provide::<tag::SmallVecValue<u8, 4>>(smallvec::smallvec![1, 2]);
+    });
+    let values = values.unwrap();
+    assert_eq!(&values[..], &[1, 2]);
+    assert!(!values.spilled());
+}
+
+#[test]
+fn request_vec_value() {
+    let values = request::<tag::VecValue<u32>, _>(|request| {
+        request.provide::<tag::VecValue<u32>>(vec![1, 2, 3]);
+    });
+    assert_eq!(values, Some(vec![1, 2, 3]));
+}
+
+#[test]
+fn request_arc_dyn() {
+    let shared: Arc<dyn Debug> = Arc::new(vec![1, 2]);
+    let handle = request::<tag::ArcDyn<dyn Debug>, _>(|request| {
+        request.provide::<tag::ArcDyn<dyn Debug>>(shared.clone());
+    });
+    assert_eq!(format!("{:?}", handle.unwrap()), "[1, 2]");
+    assert_eq!(Arc::strong_count(&shared), 1);
+}
+
+#[test]
+fn request_fn_ref() {
+    let offset = 10;
+    let add = |x: u32| x + offset;
+    let callback = request::<tag::FnRef<tag::Value<u32>, tag::Value<u32>>, _>(|request| {
+        request.provide::<tag::FnRef<tag::Value<u32>, tag::Value<u32>>>(&add);
+    });
+
+    let callback = callback.unwrap();
+    assert_eq!(callback(1), 11);
+    assert_eq!(callback(2), 12);
+}
+
+#[test]
+fn request_duration_value() {
+    let duration = request::<tag::DurationValue, _>(|request| {
+        request.provide::<tag::DurationValue>(std::time::Duration::from_secs(5));
+    });
+    assert_eq!(duration, Some(std::time::Duration::from_secs(5)));
+}
+
+#[test]
+fn request_any_box() {
+    let boxed = request::<tag::AnyBox, _>(|request| {
+        request.provide::<tag::AnyBox>(Box::new(5u32));
+    });
+    let boxed = boxed.unwrap();
+    assert!(!boxed.is::<u8>());
+    assert_eq!(boxed.downcast::<u32>().ok().map(|value| *value), Some(5));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn request_paths() {
+    use std::path::{Path, PathBuf};
+
+    let owned = PathBuf::from("/tmp/dyno");
+    let path = request::<tag::PathRef, _>(|request| {
+        request
+            .provide::<tag::PathRef>(&owned)
+            .provide_with::<tag::PathBufValue, _>(|| owned.clone());
+    });
+    assert_eq!(path, Some(Path::new("/tmp/dyno")));
+
+    let buf = request::<tag::PathBufValue, _>(|request| {
+        request.provide_with::<tag::PathBufValue, _>(|| owned.join("file"));
+    });
+    assert_eq!(buf, Some(PathBuf::from("/tmp/dyno/file")));
+}
+
+#[test]
+fn request_nullable_pointer() {
+    let value = 3u8;
+    let present = request::<tag::Nullable<tag::RawConstPtr<u8>>, _>(|request| {
+        request.provide::<tag::Nullable<tag::RawConstPtr<u8>>>(&value);
+    });
+    assert_eq!(present, Some(&value as *const u8));
+
+    let null = request::<tag::Nullable<tag::RawConstPtr<u8>>, _>(|request| {
+        request
+            .provide::<tag::Nullable<tag::RawConstPtr<u8>>>(std::ptr::null())
+            .provide::<tag::Nullable<tag::RawConstPtr<u8>>>(&value);
+    });
+    assert_eq!(null.map(<*const u8>::is_null), Some(true));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn request_lock_guards() {
+    let lock = std::sync::RwLock::new(vec![1, 2]);
+
+    let mut write = request::<tag::WriteGuard<Vec<i32>>, _>(|request| {
+        request.provide_with::<tag::WriteGuard<Vec<i32>>, _>(|| lock.write().unwrap());
+    })
+    .unwrap();
+    write.push(3);
+    drop(write);
+
+    let read = request::<tag::ReadGuard<Vec<i32>>, _>(|request| {
+        request.provide_with::<tag::ReadGuard<Vec<i32>>, _>(|| lock.read().unwrap());
+    })
+    .unwrap();
+    assert_eq!(*read, [1, 2, 3]);
+    assert!(lock.try_write().is_err());
+}
+
+#[test]
+fn request_cell_refs() {
+    let counter = std::cell::Cell::new(1u32);
+    let cell = request::<tag::CellRef<u32>, _>(|request| {
+        request.provide::<tag::CellRef<u32>>(&counter);
+    });
+    let cell = cell.unwrap();
+    cell.set(cell.get() + 1);
+    assert_eq!(counter.get(), 2);
+
+    let names = std::cell::RefCell::new(vec!["a"]);
+    let refcell = request::<tag::RefCellRef<Vec<&str>>, _>(|request| {
+        request.provide::<tag::RefCellRef<Vec<&str>>>(&names);
+    });
+    refcell.unwrap().borrow_mut().push("b");
+    assert_eq!(*names.borrow(), ["a", "b"]);
+}
+
+#[test]
+fn request_ref_ref() {
+    let value = 7u32;
+    let inner = &value;
+    let nested = request::<tag::RefRef<u32>, _>(|request| {
+        request.provide::<tag::RefRef<u32>>(&inner);
+    });
+    assert_eq!(nested.map(|value| **value), Some(7));
+}
+
+#[test]
+fn request_cow_tags() {
+    use std::borrow::Cow;
+
+    let name = String::from("borrowed");
+    let borrowed = request::<tag::CowStr, _>(|request| {
+        request.provide::<tag::CowStr>(Cow::Borrowed(&name));
+    });
+    assert!(matches!(borrowed, Some(Cow::Borrowed("borrowed"))));
+
+    let owned = request::<tag::CowStr, _>(|request| {
+        request.provide::<tag::CowStr>(Cow::Owned(name.to_uppercase()));
+    });
+    assert!(matches!(owned, Some(Cow::Owned(ref s)) if s == "BORROWED"));
+
+    let values = [1, 2, 3];
+    let borrowed = request::<tag::CowSlice<i32>, _>(|request| {
+        request.provide::<tag::CowSlice<i32>>(Cow::Borrowed(&values));
+    });
+    assert!(matches!(borrowed, Some(Cow::Borrowed(&[1, 2, 3]))));
+
+    let owned = request::<tag::CowSlice<i32>, _>(|request| {
+        request.provide::<tag::CowSlice<i32>>(Cow::Owned(values.to_vec()));
+    });
+    assert!(matches!(owned, Some(Cow::Owned(ref v)) if v == &[1, 2, 3]));
+}
+
+#[test]
+fn request_numeric_wrappers() {
+    use std::num::{NonZeroU32, Wrapping};
+
+    let nonzero = request::<tag::NonZeroValue<NonZeroU32>, _>(|request| {
+        request.provide::<tag::NonZeroValue<NonZeroU32>>(NonZeroU32::new(5).unwrap());
+    });
+    assert_eq!(nonzero.map(NonZeroU32::get), Some(5));
+
+    let wrapping = request::<tag::WrappingValue<u8>, _>(|request| {
+        request.provide::<tag::WrappingValue<u8>>(Wrapping(250));
+    });
+    assert_eq!(wrapping.map(|w| w + Wrapping(10)), Some(Wrapping(4)));
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn request_pod_from_bytes() {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Header {
+        version: u8,
+        flags: u8,
+        len: [u8; 2],
+    }
+
+    // SAFETY: `Header` is `repr(C)`, has no padding, and any bit pattern is
+    // valid for its fields.
+    unsafe impl bytemuck::Zeroable for Header {}
+    unsafe impl bytemuck::Pod for Header {}
+
+    let packet = [1u8, 0x80, 0x00, 0x10, 0xff];
+    let header = request::<tag::Pod<Header>, _>(|request| {
+        request.provide_pod_bytes::<Header>(&packet[..4]);
+    });
+    assert_eq!(
+        header,
+        Some(&Header {
+            version: 1,
+            flags: 0x80,
+            len: [0x00, 0x10],
+        })
+    );
+
+    let short = request::<tag::Pod<Header>, _>(|request| {
+        request.provide_pod_bytes::<Header>(&packet[..3]);
+    });
+    assert_eq!(short, None);
+}
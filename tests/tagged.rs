@@ -0,0 +1,155 @@
+use dyno::{tag, TagValue, Tagged};
+use std::collections::HashSet;
+
+#[test]
+fn compare_tag_values() {
+    let hello = TagValue::<tag::Ref<str>>("hello");
+    assert!(hello == TagValue::<tag::Ref<str>>("hello"));
+    assert!(hello != TagValue::<tag::Ref<str>>("world"));
+}
+
+#[test]
+fn clone_tag_value() {
+    let original = TagValue::<tag::Value<String>>("hello".to_owned());
+    let mut cloned = original.clone();
+    cloned.0.push_str(", world");
+    assert_eq!(original.0, "hello");
+    assert_eq!(cloned.0, "hello, world");
+}
+
+#[test]
+fn hash_tag_value() {
+    let mut set = HashSet::new();
+    set.insert(TagValue::<tag::Ref<str>>("hello"));
+    set.insert(TagValue::<tag::Ref<str>>("world"));
+    assert!(set.contains(&TagValue::<tag::Ref<str>>("hello")));
+    assert!(!set.contains(&TagValue::<tag::Ref<str>>("jane")));
+}
+
+#[test]
+fn map_tag_value() {
+    let value = TagValue::<tag::Value<u32>>(5);
+    let mapped: TagValue<tag::Value<String>> = value.map(|n| n.to_string());
+    assert_eq!(mapped.0, "5");
+}
+
+#[test]
+fn as_ref_tag_value() {
+    fn len<T: AsRef<String>>(value: &T) -> usize {
+        value.as_ref().len()
+    }
+
+    let mut value = TagValue::<tag::Value<String>>("hello".to_owned());
+    assert_eq!(len(&value), 5);
+    value.as_mut().push('!');
+    assert_eq!(value.as_ref(), "hello!");
+}
+
+#[test]
+fn new_and_into_inner() {
+    let value = TagValue::<tag::Ref<str>>::new("hello");
+    assert_eq!(value.into_inner(), "hello");
+}
+
+#[test]
+fn tag_id_matches_type_id() {
+    use dyno::Tagged;
+    use std::any::TypeId;
+
+    let value = TagValue::<tag::Value<u32>>(5);
+    let tagged: &dyn Tagged = &value;
+    assert_eq!(tagged.tag_id(), TypeId::of::<tag::Value<u32>>());
+    assert_ne!(tagged.tag_id(), TypeId::of::<tag::Value<u64>>());
+}
+
+#[test]
+fn same_tag_as() {
+    let a: &dyn Tagged = &TagValue::<tag::Value<i32>>(1);
+    let b: &dyn Tagged = &TagValue::<tag::Value<i32>>(2);
+    let c: &dyn Tagged = &TagValue::<tag::Ref<str>>("c");
+    assert!(a.same_tag_as(b));
+    assert!(b.same_tag_as(a));
+    assert!(!a.same_tag_as(c));
+}
+
+#[test]
+fn cast_tag_value() {
+    let text = TagValue::<tag::Ref<str>>("text");
+    let text = text.cast::<tag::Str>();
+    assert_eq!(text.0, "text");
+
+    let tagged: &dyn Tagged = &text;
+    assert!(tagged.is::<tag::Str>());
+    assert!(!tagged.is::<tag::Ref<str>>());
+}
+
+#[test]
+fn downcast_unchecked() {
+    let mut value = TagValue::<tag::Value<i32>>(1);
+    let tagged: &mut dyn Tagged = &mut value;
+    assert!(tagged.is::<tag::Value<i32>>());
+    // SAFETY: Just checked that `tagged` is tagged with `tag::Value<i32>`.
+    unsafe {
+        *tagged.downcast_mut_unchecked::<tag::Value<i32>>() += 1;
+        assert_eq!(*tagged.downcast_ref_unchecked::<tag::Value<i32>>(), 2);
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "dyno: value is not tagged with `dyno::tag::Value<u8>`")]
+fn downcast_unchecked_wrong_tag() {
+    let tagged: &dyn Tagged = &TagValue::<tag::Value<i32>>(1);
+    // SAFETY: Not actually safe, but the debug assertion fires before the
+    // value is reinterpreted.
+    unsafe {
+        tagged.downcast_ref_unchecked::<tag::Value<u8>>();
+    }
+}
+
+#[test]
+fn default_tag_value() {
+    let slot = TagValue::<tag::Optional<tag::Value<u32>>>::default();
+    assert_eq!(slot.0, None);
+    assert_eq!(TagValue::<tag::Value<String>>::default().0, "");
+}
+
+#[test]
+fn downcast_ref_or_default() {
+    let tagged: &dyn Tagged = &TagValue::<tag::Value<i32>>(1);
+    assert_eq!(*tagged.downcast_ref_or::<tag::Value<i32>>(&0), 1);
+    assert_eq!(*tagged.downcast_ref_or::<tag::Value<u8>>(&0), 0);
+}
+
+#[test]
+fn project_tuple_tags() {
+    let pair = TagValue::<tag::Pair<tag::Ref<str>, tag::Value<u32>>>(("pair", 2));
+    assert_eq!(*pair.first(), "pair");
+    assert_eq!(*pair.second(), 2);
+
+    let triple =
+        TagValue::<tag::Tuple3<tag::Value<u8>, tag::Ref<str>, tag::Unit>>((1, "triple", ()));
+    assert_eq!(*triple.first(), 1);
+    assert_eq!(*triple.second(), "triple");
+    assert_eq!(*triple.third(), ());
+
+    let quad = TagValue::<
+        tag::Tuple4<tag::Value<u8>, tag::Value<u16>, tag::Value<u32>, tag::Value<u64>>,
+    >((1, 2, 3, 4));
+    assert_eq!(*quad.fourth(), 4);
+}
+
+#[test]
+fn expect_downcast_matching() {
+    let mut value = TagValue::<tag::Value<i32>>(1);
+    let tagged: &mut dyn Tagged = &mut value;
+    *tagged.expect_downcast_mut::<tag::Value<i32>>() += 1;
+    assert_eq!(*tagged.expect_downcast_ref::<tag::Value<i32>>(), 2);
+}
+
+#[test]
+#[should_panic(expected = "expected a value tagged with `dyno::tag::Value<u8>`")]
+fn expect_downcast_mismatched() {
+    let tagged: &dyn Tagged = &TagValue::<tag::Value<i32>>(1);
+    tagged.expect_downcast_ref::<tag::Value<u8>>();
+}
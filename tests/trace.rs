@@ -0,0 +1,64 @@
+#![cfg(feature = "trace")]
+
+use dyno::provider::{self, Provider, Request};
+use dyno::{tag, trace};
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<(&'static str, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: &trace::Event) {
+    EVENTS.with(|events| events.borrow_mut().push((event.tag_name, event.matched)));
+}
+
+struct Traced;
+
+impl Provider for Traced {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request
+            .provide::<tag::Value<u32>>(1)
+            .provide::<tag::Ref<str>>("traced");
+    }
+}
+
+#[test]
+fn trace_matched_and_unmatched() {
+    trace::set_hook(Some(record));
+    let provider: Box<dyn Provider> = Box::new(Traced);
+    assert_eq!(provider.request::<tag::Ref<str>>(), Some("traced"));
+    trace::set_hook(None);
+    let _ = provider.request::<tag::Ref<str>>();
+
+    let events = EVENTS.with(|events| events.take());
+    assert_eq!(
+        events,
+        [
+            ("dyno::tag::Value<u32>", false),
+            ("dyno::tag::Ref<str>", true),
+        ]
+    );
+
+    // Checks by request id and keyed and argument provisions are traced too.
+    trace::set_hook(Some(record));
+    let key = provider::request_keyed::<tag::Value<u32>, _, _>("key", |request| {
+        let _ = request.matches_any(&[provider::req_tag_id::<tag::Value<u32>>()]);
+        request.provide_keyed::<tag::Value<u32>, _>(&"key", 2);
+    });
+    let arg = provider::request_with_arg::<tag::Value<u32>, tag::Value<u64>, _>(3, |request| {
+        request.provide_computed_from::<tag::Value<u32>, tag::Value<u64>, _>(|&arg| arg.into());
+    });
+    trace::set_hook(None);
+    assert_eq!(key, Some(2));
+    assert_eq!(arg, Some(3));
+
+    let events = EVENTS.with(|events| events.take());
+    assert_eq!(
+        events,
+        [
+            (trace::UNKNOWN_TAG, true),
+            ("dyno::tag::Value<u32>", true),
+            ("dyno::tag::Value<u64>", true),
+        ]
+    );
+}